@@ -0,0 +1,47 @@
+use chrono::NaiveDate;
+
+use crate::backend::classes::occurrence::Occurrence;
+
+/// Occurrences in a date range grouped by day, with incomplete items whose
+/// time has already passed pulled out in front.
+pub struct Agenda {
+    pub overdue: Vec<Occurrence>,
+    /// Every day of the range in order, each with the occurrences touching
+    /// it sorted by start time. Multi-day occurrences appear on every day.
+    pub days: Vec<(NaiveDate, Vec<Occurrence>)>,
+}
+
+impl Agenda {
+    pub fn is_empty(&self) -> bool {
+        self.overdue.is_empty() && self.days.iter().all(|(_, occurrences)| occurrences.is_empty())
+    }
+
+    /// Overdue items first, then each occurrence once in start order.
+    pub fn occurrences(&self) -> Vec<Occurrence> {
+        let mut occurrences = self.overdue.clone();
+        for occurrence in self.days.iter().flat_map(|(_, occurrences)| occurrences) {
+            let seen = occurrences.iter().any(|o| {
+                o.event.get_id() == occurrence.event.get_id() && o.original_start == occurrence.original_start
+            });
+            if !seen {
+                occurrences.push(occurrence.clone());
+            }
+        }
+        occurrences
+    }
+
+    pub fn display(&self) {
+        if self.is_empty() {
+            println!("No events found!");
+            return;
+        }
+        if !self.overdue.is_empty() {
+            println!("\n=== Overdue ===");
+            self.overdue.iter().for_each(Occurrence::display);
+        }
+        for (date, occurrences) in self.days.iter().filter(|(_, o)| !o.is_empty()) {
+            println!("\n=== {} ===", date.format("%A %Y-%m-%d"));
+            occurrences.iter().for_each(Occurrence::display);
+        }
+    }
+}
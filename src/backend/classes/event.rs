@@ -1,21 +1,43 @@
-use chrono::NaiveDateTime;
+use chrono::{Days, NaiveDateTime};
+use chrono_tz::Tz;
 use serde::{Serialize, Deserialize};
+use std::fmt;
+use uuid::Uuid;
 use crate::backend::enums::reoccurance::Reoccurance;
 use crate::backend::enums::priority::Priority;
+use crate::backend::classes::occurrence::{OccurrenceOverride, OccurrenceState};
+use crate::backend::time_zone;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     id: usize,
+    // Globally unique across machines; nil until assigned for legacy events
+    #[serde(default)]
+    uid: Uuid,
     name: String,
+    // UTC instants when `time_zone` is set, otherwise floating wall-clock
+    // times that read the same in every zone
     start_time: NaiveDateTime,
     end_time: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    time_zone: Option<Tz>,
+    // Date-only event covering start_time's date through end_time's date,
+    // both held at midnight
+    #[serde(default)]
+    all_day: bool,
     priority: Priority,
     reoccurance: Reoccurance,
     note: String,
     completed: bool,
+    // Completion, skips and moves of single occurrences of a recurring event
+    #[serde(default, skip_serializing_if = "OccurrenceState::is_empty")]
+    occurrences: OccurrenceState,
 }
 
 impl Event {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: usize,
         name: String,
@@ -33,101 +55,200 @@ impl Event {
 
         Self {
             id,
+            uid: Uuid::new_v4(),
             name,
             start_time,
             end_time,
+            time_zone: None,
+            all_day: false,
             priority,
             reoccurance,
             note,
             completed,
+            occurrences: OccurrenceState::default(),
         }
     }
     
     // Helpers
     pub fn display(&self) {
+        self.display_at(&self.name, self.local_start(), self.local_end(), self.completed);
+    }
+
+    /// Displays the event with the details of one of its occurrences. Times
+    /// are expected in the viewer's zone.
+    pub fn display_at(
+        &self,
+        name: &str,
+        start_time: NaiveDateTime,
+        end_time: NaiveDateTime,
+        completed: bool,
+    ) {
         println!(" __________________________________________");
         println!("| Event ID: {}", self.id);
-        println!("| Event: {}", self.name);
-        println!("| Start Time: {}", self.start_time);
-        println!("| End Time: {}", self.end_time);
-        println!("| Priority: {}", format!("{:?}", self.priority));
-        println!("| Reoccurance: {}", format!("{:?}", self.reoccurance));
+        println!("| Event: {}", name);
+        if self.all_day {
+            if start_time.date() == end_time.date() {
+                println!("| Date: {} (all day)", start_time.date());
+            } else {
+                println!("| Dates: {} to {} (all day)", start_time.date(), end_time.date());
+            }
+        } else {
+            println!("| Start Time: {}", start_time);
+            println!("| End Time: {}", end_time);
+            match self.time_zone {
+                Some(tz) => println!("| Time Zone: {}", tz.name()),
+                None => println!("| Time Zone: floating"),
+            }
+        }
+        println!("| Priority: {:?}", self.priority);
+        println!("| Reoccurance: {}", self.reoccurance);
         println!("| Note: {}", self.note);
-        println!("| Completed: {}", self.completed);
+        println!("| Completed: {}", completed);
         println!(" __________________________________________");
     }
 
-    pub fn to_string(&self) -> String {
-        format!(
-            "{}|{}|{}|{}|{}|{}|{}|{}",
-            self.id,
-            self.name,
-            self.start_time.format("%Y-%m-%d %H:%M:%S"),
-            self.end_time.format("%Y-%m-%d %H:%M:%S"),
-            format!("{:?}", self.priority),
-            format!("{:?}", self.reoccurance),
-            self.note,
-            self.completed
-        )
+    /// Parses a line written by this version's `Display` impl, where `\\`,
+    /// `\|`, `\n` and `\r` escape characters inside fields. Lines without
+    /// the trailing uid field come back with a nil uid. Optional trailing
+    /// fields hold per-occurrence state as JSON, the IANA time zone and the
+    /// all-day flag.
+    pub fn from_string(s: &str) -> Option<Event> {
+        let parts = split_escaped(s);
+        if !(8..=12).contains(&parts.len()) {
+            return None;
+        }
+        let mut event = Self::from_fields(
+            &parts[0], &parts[1], &parts[2], &parts[3], &parts[4], &parts[5], &parts[6], &parts[7],
+        )?;
+        if let Some(uid) = parts.get(8) {
+            event.set_uid(Uuid::parse_str(uid).ok()?);
+        }
+        if let Some(occurrences) = parts.get(9) {
+            event.occurrences = OccurrenceState::from_json(occurrences)
+                .inspect_err(|e| eprintln!("Error parsing occurrences: {}", e))
+                .ok()?;
+        }
+        if let Some(tz) = parts.get(10).filter(|tz| !tz.is_empty()) {
+            event.restore_time_zone(Some(tz.parse().ok()?));
+        }
+        if let Some(all_day) = parts.get(11) {
+            event.all_day = all_day == "true";
+        }
+        Some(event)
     }
 
-    pub fn from_string(s: &str) -> Option<Event> {
+    /// Parses an unescaped line from a planner written before fields were
+    /// escaped. Pipes inside the name or note are recovered by locating the
+    /// fixed-format time, priority and reoccurance columns.
+    pub fn from_legacy_string(s: &str) -> Option<Event> {
         let parts: Vec<&str> = s.split('|').collect();
-        if parts.len() != 8 {
+        if parts.len() < 8 {
             return None;
         }
+        if parts.len() == 8 {
+            return Self::from_fields(
+                parts[0], parts[1], parts[2], parts[3], parts[4], parts[5], parts[6], parts[7],
+            );
+        }
 
-        let id = parts[0].parse::<usize>().ok()?;
-        let name = parts[1].to_string();
-        
-        let start_time = NaiveDateTime::parse_from_str(parts[2], "%Y-%m-%d %H:%M:%S")
-            .map_err(|e| {
-                eprintln!("Error parsing start time: {}", e);
-                e
-            }).ok()?;
-            
-        let end_time = NaiveDateTime::parse_from_str(parts[3], "%Y-%m-%d %H:%M:%S")
-            .map_err(|e| {
-                eprintln!("Error parsing end time: {}", e);
-                e
-            }).ok()?;
-
-        let priority = match parts[4] {
-            "High" => Priority::High,
-            "Medium" => Priority::Medium,
-            "Low" => Priority::Low,
-            _ => Priority::Medium,
-        };
-
-        let reoccurance = match parts[5] {
-            "None" => Reoccurance::None,
-            "Daily" => Reoccurance::Daily,
-            "Weekly" => Reoccurance::Weekly,
-            "Monthly" => Reoccurance::Monthly,
-            "Yearly" => Reoccurance::Yearly,
-            "Fornite" => Reoccurance::Fornite,
-            _ => Reoccurance::None,
-        };
-
-        let note = parts[6].to_string();
-        let completed = parts[7].parse::<bool>().unwrap_or(false);
-
-        Some(Event::new(id, name, start_time, end_time, priority, reoccurance, note, completed))
+        let last = parts.len() - 1;
+        let start = (2..=last - 4).find(|&i| {
+            NaiveDateTime::parse_from_str(parts[i], TIME_FORMAT).is_ok()
+                && NaiveDateTime::parse_from_str(parts[i + 1], TIME_FORMAT).is_ok()
+                && parts[i + 2].parse::<Priority>().is_ok()
+                && parts[i + 3].parse::<Reoccurance>().is_ok()
+        })?;
+        let name = parts[1..start].join("|");
+        let note = parts[start + 4..last].join("|");
+        Self::from_fields(
+            parts[0],
+            &name,
+            parts[start],
+            parts[start + 1],
+            parts[start + 2],
+            parts[start + 3],
+            &note,
+            parts[last],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn from_fields(
+        id: &str,
+        name: &str,
+        start_time: &str,
+        end_time: &str,
+        priority: &str,
+        reoccurance: &str,
+        note: &str,
+        completed: &str,
+    ) -> Option<Event> {
+        let id = id.parse::<usize>().ok()?;
+
+        let start_time = NaiveDateTime::parse_from_str(start_time, TIME_FORMAT)
+            .inspect_err(|e| eprintln!("Error parsing start time: {}", e))
+            .ok()?;
+
+        let end_time = NaiveDateTime::parse_from_str(end_time, TIME_FORMAT)
+            .inspect_err(|e| eprintln!("Error parsing end time: {}", e))
+            .ok()?;
+
+        let priority = priority.parse().unwrap_or(Priority::Medium);
+        let reoccurance = reoccurance.parse().unwrap_or(Reoccurance::None);
+        let completed = completed.parse::<bool>().unwrap_or(false);
+
+        let mut event = Event::new(
+            id,
+            name.to_string(),
+            start_time,
+            end_time,
+            priority,
+            reoccurance,
+            note.to_string(),
+            completed,
+        );
+        // Callers fill in the uid when the line carries one
+        event.set_uid(Uuid::nil());
+        Some(event)
     }
 
     // Getters
     pub fn get_id(&self) -> &usize {
         &self.id
     }
+    pub fn get_uid(&self) -> &Uuid {
+        &self.uid
+    }
     pub fn get_name(&self) -> &String {
         &self.name
     }
-    pub fn get_start_time(&self) -> &NaiveDateTime {
+    /// Wall-clock start in the event's own zone.
+    pub fn get_start_time(&self) -> NaiveDateTime {
+        self.stored_to_wall(self.start_time)
+    }
+    /// Wall-clock end in the event's own zone.
+    pub fn get_end_time(&self) -> NaiveDateTime {
+        self.stored_to_wall(self.end_time)
+    }
+    /// Stored start: a UTC instant for zoned events, wall-clock otherwise.
+    pub fn get_start_instant(&self) -> &NaiveDateTime {
         &self.start_time
     }
-    pub fn get_end_time(&self) -> &NaiveDateTime {
+    pub fn get_end_instant(&self) -> &NaiveDateTime {
         &self.end_time
     }
+    pub fn get_time_zone(&self) -> Option<Tz> {
+        self.time_zone
+    }
+    pub fn is_all_day(&self) -> bool {
+        self.all_day
+    }
+    pub fn get_priority(&self) -> &Priority {
+        &self.priority
+    }
+    pub fn is_recurring(&self) -> bool {
+        !matches!(self.reoccurance, Reoccurance::None)
+    }
     pub fn get_reoccurance(&self) -> &Reoccurance {
         &self.reoccurance
     }
@@ -137,16 +258,60 @@ impl Event {
     pub fn get_completed(&self) -> bool {
         self.completed
     }
+    pub fn get_occurrences(&self) -> &OccurrenceState {
+        &self.occurrences
+    }
 
     // Setters
+    pub fn set_id(&mut self, new_id: usize) {
+        self.id = new_id;
+    }
+    pub fn set_uid(&mut self, new_uid: Uuid) {
+        self.uid = new_uid;
+    }
     pub fn set_name(&mut self, new_name: String) {
         self.name = new_name;
     }
-    pub fn set_start_time(&mut self, new_start_time: NaiveDateTime) {
-        self.start_time = new_start_time;
+    /// Takes a wall-clock time in the event's zone. Ambiguous times resolve
+    /// to the earlier instant and times in a DST gap are shifted forward.
+    pub fn set_start_time(&mut self, new_start: NaiveDateTime) {
+        self.start_time = self.wall_to_stored(new_start);
+    }
+    pub fn set_end_time(&mut self, new_end: NaiveDateTime) {
+        self.end_time = self.wall_to_stored(new_end);
+    }
+    /// Takes an exact UTC instant, for DST-ambiguous times the caller has
+    /// already resolved. Only meaningful for zoned events.
+    pub fn set_start_instant(&mut self, new_start: NaiveDateTime) {
+        self.start_time = new_start;
+    }
+    pub fn set_end_instant(&mut self, new_end: NaiveDateTime) {
+        self.end_time = new_end;
+    }
+    /// Moves the event to another zone (or makes it floating) keeping its
+    /// wall-clock times.
+    pub fn set_time_zone(&mut self, new_time_zone: Option<Tz>) {
+        let (start, end) = (self.get_start_time(), self.get_end_time());
+        self.time_zone = new_time_zone;
+        self.start_time = self.wall_to_stored(start);
+        self.end_time = self.wall_to_stored(end);
+    }
+    /// All-day events are floating and snapped to midnight.
+    pub fn set_all_day(&mut self, new_all_day: bool) {
+        if new_all_day {
+            self.set_time_zone(None);
+            self.start_time = self.start_time.date().and_hms_opt(0, 0, 0).unwrap();
+            self.end_time = self.end_time.date().and_hms_opt(0, 0, 0).unwrap();
+        }
+        self.all_day = new_all_day;
     }
-    pub fn set_end_time(&mut self, new_end_time: NaiveDateTime) {
-        self.end_time = new_end_time;
+    /// Sets the zone without touching the stored times, for storage backends
+    /// whose start and end already hold instants.
+    pub fn restore_time_zone(&mut self, time_zone: Option<Tz>) {
+        self.time_zone = time_zone;
+    }
+    pub fn set_priority(&mut self, new_priority: Priority) {
+        self.priority = new_priority;
     }
     pub fn set_reoccurance(&mut self, new_reoccurance: Reoccurance) {
         self.reoccurance = new_reoccurance;
@@ -157,4 +322,224 @@ impl Event {
     pub fn set_completed(&mut self, new_completed: bool) {
         self.completed = new_completed;
     }
-}
\ No newline at end of file
+    pub fn set_occurrences(&mut self, new_occurrences: OccurrenceState) {
+        self.occurrences = new_occurrences;
+    }
+
+    /// Start in the viewer's zone, exact even for DST-ambiguous times.
+    pub fn local_start(&self) -> NaiveDateTime {
+        self.stored_to_local(self.start_time)
+    }
+    pub fn local_end(&self) -> NaiveDateTime {
+        self.stored_to_local(self.end_time)
+    }
+
+    fn stored_to_local(&self, stored: NaiveDateTime) -> NaiveDateTime {
+        match self.time_zone {
+            Some(_) => time_zone::utc_to_local(stored),
+            None => stored,
+        }
+    }
+
+    /// Whether an occurrence running from `start` to `end` (viewer's zone)
+    /// overlaps `[from, to)`. All-day events cover the whole of their last
+    /// date, and zero-length events count where they start.
+    pub fn spans(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> bool {
+        let end = if self.all_day { end + Days::new(1) } else { end };
+        start < to && (end > from || start >= from)
+    }
+
+    /// Converts a wall-clock time in the event's zone to the viewer's zone.
+    pub fn to_local(&self, wall: NaiveDateTime) -> NaiveDateTime {
+        match self.time_zone {
+            Some(tz) => time_zone::utc_to_local(time_zone::wall_to_utc(tz, wall)),
+            None => wall,
+        }
+    }
+
+    fn stored_to_wall(&self, stored: NaiveDateTime) -> NaiveDateTime {
+        match self.time_zone {
+            Some(tz) => time_zone::utc_to_wall(tz, stored),
+            None => stored,
+        }
+    }
+
+    fn wall_to_stored(&self, wall: NaiveDateTime) -> NaiveDateTime {
+        match self.time_zone {
+            Some(tz) => time_zone::wall_to_utc(tz, wall),
+            None => wall,
+        }
+    }
+
+    // Per-occurrence edits, keyed by the occurrence's original start
+    pub fn set_occurrence_completed(&mut self, original_start: NaiveDateTime, completed: bool) {
+        if completed {
+            self.occurrences.completed.insert(original_start);
+        } else {
+            self.occurrences.completed.remove(&original_start);
+        }
+    }
+    pub fn skip_occurrence(&mut self, original_start: NaiveDateTime) {
+        self.occurrences.exceptions.insert(original_start);
+    }
+    pub fn override_occurrence(&mut self, original_start: NaiveDateTime, new_override: OccurrenceOverride) {
+        self.occurrences.overrides.insert(original_start, new_override);
+    }
+    /// Undoes any skip or override of the occurrence.
+    pub fn reset_occurrence(&mut self, original_start: NaiveDateTime) {
+        self.occurrences.exceptions.remove(&original_start);
+        self.occurrences.overrides.remove(&original_start);
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}|{}|{}|{:?}|{}|{}|{}|{}|{}|{}|{}",
+            self.id,
+            escape_field(&self.name),
+            self.start_time.format(TIME_FORMAT),
+            self.end_time.format(TIME_FORMAT),
+            self.priority,
+            self.reoccurance,
+            escape_field(&self.note),
+            self.completed,
+            self.uid,
+            escape_field(&self.occurrences.to_json()),
+            self.time_zone.map(|tz| tz.name()).unwrap_or(""),
+            self.all_day
+        )
+    }
+}
+
+/// Escapes `\\`, `|` and line breaks so a field fits on one text planner line.
+pub fn escape_field(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '|' => escaped.push_str("\\|"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// Splits a text planner line into its unescaped fields.
+pub fn split_escaped(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => field.push('\n'),
+                Some('r') => field.push('\r'),
+                Some(other) => field.push(other),
+                None => field.push('\\'),
+            },
+            '|' => fields.push(String::new()),
+            other => field.push(other),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn event(name: &str, note: &str) -> Event {
+        Event::new(
+            7,
+            name.to_string(),
+            at("2026-10-20 09:00"),
+            at("2026-10-20 10:00"),
+            Priority::High,
+            Reoccurance::Weekly,
+            note.to_string(),
+            true,
+        )
+    }
+
+    #[test]
+    fn fields_round_trip_through_escaping() {
+        let fields = [
+            "plain", "a|b", "back\\slash", "trailing\\", "two\nlines", "\r\n|\\|", "\\n is not a newline", "",
+        ];
+        for field in fields {
+            let line = format!("{}|{}", escape_field(field), escape_field(field));
+            assert!(!line.contains('\n') && !line.contains('\r'));
+            assert_eq!(split_escaped(&line), [field, field]);
+        }
+    }
+
+    #[test]
+    fn names_and_notes_with_special_characters_survive_a_line() {
+        let original = event("Pay | split \\ bill", "Line one\nLine two | with \\ and \r\n");
+        let line = original.to_string();
+        assert!(!line.contains('\n'));
+
+        let parsed = Event::from_string(&line).unwrap();
+        assert_eq!(*parsed.get_id(), 7);
+        assert_eq!(parsed.get_name(), original.get_name());
+        assert_eq!(parsed.get_note(), original.get_note());
+        assert_eq!(parsed.get_start_time(), original.get_start_time());
+        assert_eq!(parsed.get_end_time(), original.get_end_time());
+        assert_eq!(parsed.get_uid(), original.get_uid());
+        assert!(parsed.get_completed());
+    }
+
+    #[test]
+    fn legacy_lines_with_pipes_in_the_name_and_note() {
+        let line = "3|Rock | Roll|2026-10-20 09:00:00|2026-10-20 10:00:00|Low|Daily|bring | cables|false";
+        let event = Event::from_legacy_string(line).unwrap();
+        assert_eq!(*event.get_id(), 3);
+        assert_eq!(event.get_name(), "Rock | Roll");
+        assert_eq!(event.get_note(), "bring | cables");
+        assert_eq!(event.get_start_time(), at("2026-10-20 09:00"));
+        assert!(matches!(event.get_priority(), Priority::Low));
+        assert!(matches!(event.get_reoccurance(), Reoccurance::Daily));
+        assert!(event.get_uid().is_nil());
+
+        let plain = "4|Gym|2026-10-20 18:00:00|2026-10-20 19:00:00|Medium|None||true";
+        assert_eq!(Event::from_legacy_string(plain).unwrap().get_name(), "Gym");
+        assert!(Event::from_legacy_string("5|too|few").is_none());
+    }
+
+    #[test]
+    fn every_line_length_from_string_accepts() {
+        let uid = Uuid::new_v4();
+        let base = "1|Gym|2026-10-20 18:00:00|2026-10-20 19:00:00|Medium|None|note|false";
+        let lines = [
+            base.to_string(),
+            format!("{}|{}", base, uid),
+            format!("{}|{}|{}", base, uid, r#"{"completed":["2026-10-27T18:00:00"]}"#),
+            format!("{}|{}||Europe/Paris", base, uid),
+            format!("{}|{}|||true", base, uid),
+        ];
+        let events: Vec<Event> = lines.iter().map(|line| Event::from_string(line).unwrap()).collect();
+        assert!(events[0].get_uid().is_nil());
+        assert!(events[1..].iter().all(|e| *e.get_uid() == uid));
+        assert!(events[2].get_occurrences().completed.contains(&at("2026-10-27 18:00")));
+        assert_eq!(events[3].get_time_zone(), Some(chrono_tz::Europe::Paris));
+        assert!(events[4].is_all_day() && !events[3].is_all_day());
+
+        assert!(Event::from_string("1|Gym|2026-10-20 18:00:00").is_none());
+        assert!(Event::from_string(&format!("{}|{}|||true|extra", base, uid)).is_none());
+        assert!(Event::from_string(&format!("{}|not-a-uid", base)).is_none());
+    }
+}
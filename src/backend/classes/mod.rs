@@ -1,2 +1,5 @@
+pub mod agenda;
 pub mod planner;
-pub mod event;
\ No newline at end of file
+pub mod event;
+pub mod occurrence;
+pub mod recurrence_rule;
\ No newline at end of file
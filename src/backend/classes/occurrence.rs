@@ -0,0 +1,215 @@
+use chrono::{Days, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::classes::event::Event;
+
+/// One concrete instance of an event, with its times in the viewer's zone.
+/// Non-recurring events have exactly one occurrence; recurring ones have one
+/// per repetition.
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub event: Event,
+    /// Wall-clock start the rule generated, in the event's zone. Per-occurrence
+    /// edits are keyed by it, even after the occurrence has been moved.
+    pub original_start: NaiveDateTime,
+    pub name: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub completed: bool,
+}
+
+impl Occurrence {
+    pub fn display(&self) {
+        self.event.display_at(&self.name, self.start, self.end, self.completed);
+    }
+}
+
+/// Replacement time and optionally name for a single occurrence of a
+/// recurring event. Times are wall-clock in the event's zone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccurrenceOverride {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Per-occurrence bookkeeping for recurring events, keyed by each
+/// occurrence's original wall-clock start in the event's zone.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OccurrenceState {
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub completed: BTreeSet<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub exceptions: BTreeSet<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<NaiveDateTime, OccurrenceOverride>,
+}
+
+impl OccurrenceState {
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty() && self.exceptions.is_empty() && self.overrides.is_empty()
+    }
+
+    /// Compact JSON used by the text and SQLite backends; empty when there
+    /// is nothing to record.
+    pub fn to_json(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(json)
+    }
+}
+
+/// Wall-clock start times (in the event's zone) that the event's reoccurance
+/// generates in `[from, to)`, before exceptions and overrides are applied.
+pub fn original_starts(event: &Event, from: NaiveDateTime, to: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let start = event.get_start_time();
+    match event.get_reoccurance().to_rule() {
+        Some(rule) => rule.starts_between(start, from, to),
+        None if start >= from && start < to => vec![start],
+        None => Vec::new(),
+    }
+}
+
+/// Every occurrence of `event` overlapping `[from, to)` of the viewer's
+/// zone, in start order. Skipped occurrences are left out and moved ones
+/// appear at their new time.
+pub fn expand(event: &Event, from: NaiveDateTime, to: NaiveDateTime) -> Vec<Occurrence> {
+    let first_start = event.get_start_time();
+    let duration = event.get_end_time() - first_start;
+    let state = event.get_occurrences();
+    let occurrence = |original_start: NaiveDateTime, start, end, name: Option<&String>| {
+        // The first occurrence keeps the exact instant it was created with,
+        // which matters when its wall-clock time is DST-ambiguous
+        let (start, end) = if original_start == first_start && start == first_start {
+            (event.local_start(), event.local_end())
+        } else {
+            (event.to_local(start), event.to_local(end))
+        };
+        Occurrence {
+            event: event.clone(),
+            original_start,
+            name: name.unwrap_or(event.get_name()).clone(),
+            start,
+            end,
+            completed: event.get_completed() || state.completed.contains(&original_start),
+        }
+    };
+
+    // The event's zone can be up to a day away from the viewer's, and
+    // multi-day occurrences start before the window, so expand a wider one
+    // and trim once the times are converted.
+    let lookback = from - duration - Days::new(2);
+    let mut occurrences: Vec<Occurrence> = original_starts(event, lookback, to + Days::new(1))
+        .into_iter()
+        .filter(|s| !state.exceptions.contains(s) && !state.overrides.contains_key(s))
+        .map(|s| occurrence(s, s, s + duration, None))
+        .collect();
+    for (original_start, moved) in &state.overrides {
+        if !state.exceptions.contains(original_start) {
+            occurrences.push(occurrence(*original_start, moved.start, moved.end, moved.name.as_ref()));
+        }
+    }
+    occurrences.retain(|o| event.spans(o.start, o.end, from, to));
+    occurrences.sort_by_key(|o| o.start);
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::enums::priority::Priority;
+    use crate::backend::enums::reoccurance::Reoccurance;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn weekly(start: &str, end: &str) -> Event {
+        let (start, end) = (at(start), at(end));
+        let name = "Standup".to_string();
+        Event::new(0, name, start, end, Priority::Medium, Reoccurance::Weekly, String::new(), false)
+    }
+
+    fn starts(occurrences: &[Occurrence]) -> Vec<NaiveDateTime> {
+        occurrences.iter().map(|o| o.start).collect()
+    }
+
+    #[test]
+    fn one_off_event_has_one_occurrence() {
+        let mut event = weekly("2026-10-19 09:00", "2026-10-19 09:15");
+        event.set_reoccurance(Reoccurance::None);
+        let occurrences = expand(&event, at("2026-10-01 00:00"), at("2026-12-01 00:00"));
+        assert_eq!(starts(&occurrences), [at("2026-10-19 09:00")]);
+    }
+
+    #[test]
+    fn occurrences_keep_the_event_duration() {
+        let event = weekly("2026-10-19 09:00", "2026-10-19 09:15");
+        let occurrences = expand(&event, at("2026-10-20 00:00"), at("2026-11-03 00:00"));
+        assert_eq!(starts(&occurrences), [at("2026-10-26 09:00"), at("2026-11-02 09:00")]);
+        assert!(occurrences.iter().all(|o| o.end - o.start == chrono::Duration::minutes(15)));
+    }
+
+    #[test]
+    fn occurrence_running_into_the_window_is_included() {
+        let event = weekly("2026-10-19 22:00", "2026-10-20 02:00");
+        let occurrences = expand(&event, at("2026-10-20 00:00"), at("2026-10-21 00:00"));
+        assert_eq!(starts(&occurrences), [at("2026-10-19 22:00")]);
+    }
+
+    #[test]
+    fn skipped_occurrences_are_left_out() {
+        let mut event = weekly("2026-10-19 09:00", "2026-10-19 09:15");
+        event.skip_occurrence(at("2026-10-26 09:00"));
+        let occurrences = expand(&event, at("2026-10-19 00:00"), at("2026-11-03 00:00"));
+        assert_eq!(starts(&occurrences), [at("2026-10-19 09:00"), at("2026-11-02 09:00")]);
+    }
+
+    #[test]
+    fn moved_occurrences_appear_at_their_new_time() {
+        let mut event = weekly("2026-10-19 09:00", "2026-10-19 09:15");
+        let moved = OccurrenceOverride {
+            start: at("2026-10-28 14:00"),
+            end: at("2026-10-28 15:00"),
+            name: Some("Retro".to_string()),
+        };
+        event.override_occurrence(at("2026-10-26 09:00"), moved);
+        let occurrences = expand(&event, at("2026-10-19 00:00"), at("2026-11-03 00:00"));
+        assert_eq!(
+            starts(&occurrences),
+            [at("2026-10-19 09:00"), at("2026-10-28 14:00"), at("2026-11-02 09:00")]
+        );
+        assert_eq!(occurrences[1].name, "Retro");
+        assert_eq!(occurrences[1].original_start, at("2026-10-26 09:00"));
+    }
+
+    #[test]
+    fn completion_is_per_occurrence() {
+        let mut event = weekly("2026-10-19 09:00", "2026-10-19 09:15");
+        event.set_occurrence_completed(at("2026-10-26 09:00"), true);
+        let occurrences = expand(&event, at("2026-10-19 00:00"), at("2026-11-03 00:00"));
+        let completed: Vec<bool> = occurrences.iter().map(|o| o.completed).collect();
+        assert_eq!(completed, [false, true, false]);
+    }
+
+    #[test]
+    fn state_round_trips_through_json() {
+        assert_eq!(OccurrenceState::default().to_json(), "");
+        let mut state = OccurrenceState::default();
+        state.completed.insert(at("2026-10-19 09:00"));
+        state.exceptions.insert(at("2026-10-26 09:00"));
+        assert_eq!(OccurrenceState::from_json(&state.to_json()).unwrap(), state);
+        assert_eq!(OccurrenceState::from_json("").unwrap(), OccurrenceState::default());
+    }
+}
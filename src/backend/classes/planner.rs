@@ -1,145 +1,404 @@
-use chrono::NaiveDateTime;
+use chrono::{Days, NaiveDate, NaiveDateTime};
 
-use std::fs::{File, OpenOptions};
-use std::io::{self, BufRead, BufReader, Write};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
 
+use crate::backend::classes::agenda::Agenda;
 use crate::backend::classes::event::Event;
-use crate::backend::enums::priority::Priority;
-use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::classes::occurrence::{self, Occurrence};
+use crate::backend::enums::sort_key::SortKey;
+use crate::backend::exchange::{self, ImportReport, Parsed};
+use crate::backend::filter::Filter;
+use crate::backend::search::{self, SearchHit};
+use crate::backend::enums::storage_format::StorageFormat;
+use crate::backend::storage::migration::{self, MigrationReport};
+use crate::backend::storage::{self, Storage, backup};
+
+/// How far back the agenda looks for incomplete items that are overdue.
+const OVERDUE_LOOKBACK_DAYS: u64 = 30;
 
 pub struct Planner {
     name: String,
     events: Vec<Event>,
+    // Event id -> position in `events`
+    index: HashMap<usize, usize>,
     next_event_id: usize,
     event_count: u32,
-    file_name: String
+    storage: Box<dyn Storage>,
+    // Whether this session has snapshotted the planner file yet
+    backed_up: bool,
+    // Whether an edit failed to reach storage and only lives in `events`
+    unsaved: bool,
 }
 
 impl Planner {
-    pub fn new(name: String) -> Self {
-        let file_name = format!("src/planners/{}.txt", name);
+    pub fn new(name: String) -> io::Result<Self> {
+        let storage = storage::open_planner(&name)?;
+        Ok(Self::with_storage(name, storage))
+    }
+
+    pub fn with_storage(name: String, storage: Box<dyn Storage>) -> Self {
         Self {
             name,
             events: Vec::new(),
+            index: HashMap::new(),
             next_event_id: 0,
             event_count: 0,
-            file_name,
+            storage,
+            backed_up: false,
+            unsaved: false,
         }
     }
 
-    pub fn add_event(&mut self, new_event: Event) {
+    /// Snapshots the planner file once per session, right before the first
+    /// change, so runs that only read never rotate older backups out.
+    fn back_up_before_change(&mut self) -> io::Result<()> {
+        if !self.backed_up {
+            backup::create_backup(self.storage.file_name())?;
+            self.backed_up = true;
+        }
+        Ok(())
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    /// Adds an event under its own id, handing out a fresh one if that id is
+    /// already taken. Returns the id the event ended up with.
+    pub fn add_event(&mut self, mut new_event: Event) -> usize {
+        let mut id = *new_event.get_id();
+        if self.index.contains_key(&id) {
+            println!("Duplicate event id {}, reassigning to {}.", id, self.next_event_id);
+            id = self.next_event_id;
+            new_event.set_id(id);
+        }
+        self.index.insert(id, self.events.len());
         self.events.push(new_event);
-        self.next_event_id += 1;
+        self.next_event_id = self.next_event_id.max(id + 1);
         self.event_count += 1;
+        id
     }
 
-    pub fn delete_event(&mut self, event_id: usize) -> Option<Event> {
-        if event_id < self.events.len() {
-            self.event_count -= 1;
-            if let Err(e) = self.delete_event_in_file(event_id) {
-                eprintln!("Failed to delete event in file: {}", e);
-            }
-            Some(self.events.remove(event_id))
-        } else {
+    /// Removes the event from storage and then from the planner. Returns
+    /// `None` for an unknown id; when storage fails the event is kept.
+    pub fn delete_event(&mut self, event_id: usize) -> io::Result<Option<Event>> {
+        let Some(&position) = self.index.get(&event_id) else {
             println!("Invalid event id {}.", event_id);
-            None
-        }
-    }
-
-    pub fn create_event(
-        &mut self,
-        name: String,
-        start_time: NaiveDateTime,
-        end_time: NaiveDateTime,
-        priority: Priority,
-        reoccurance: Reoccurance,
-        note: String,
-        completed: bool,
-    ) {
-        let event = Event::new(
-            self.next_event_id,
-            name,
-            start_time,
-            end_time,
-            priority,
-            reoccurance,
-            note,
-            completed,
-        );
-        if let Err(e) = self.append_event_to_file(&event) {
-            eprintln!("Failed to append event to file: {}", e);
+            return Ok(None);
+        };
+        self.back_up_before_change()?;
+        self.storage.delete(event_id)?;
+        self.index.remove(&event_id);
+        self.event_count -= 1;
+        let removed = self.events.remove(position);
+        for event in &self.events[position..] {
+            *self.index.get_mut(event.get_id()).unwrap() -= 1;
+        }
+        Ok(Some(removed))
+    }
+
+    /// Gives `event` the next free id, persists it and adds it to the
+    /// planner. Returns the assigned id; when storage fails the event isn't
+    /// added.
+    pub fn create_event(&mut self, mut event: Event) -> io::Result<usize> {
+        event.set_id(self.next_event_id);
+        self.back_up_before_change()?;
+        self.storage.insert(&event)?;
+        Ok(self.add_event(event))
+    }
+
+    /// Adds events read from another tool's file. Events whose uid is
+    /// already in the planner, or with the same name and times as one that
+    /// is, are duplicates: skipped, or with `update` written over the
+    /// existing event (keeping its id) when the uid matches.
+    pub fn import_events(&mut self, parsed: Parsed, update: bool) -> ImportReport {
+        let mut report = ImportReport { problems: parsed.problems, ..ImportReport::default() };
+        for mut event in parsed.events {
+            let same_uid = self.events.iter().find(|e| e.get_uid() == event.get_uid()).map(|e| *e.get_id());
+            match same_uid {
+                Some(id) if update => {
+                    event.set_id(id);
+                    *self.find_event_mut(id).unwrap() = event;
+                    match self.update_event(id) {
+                        Ok(()) => report.updated += 1,
+                        Err(e) => {
+                            report.failed += 1;
+                            report.problems.push(format!("Failed to update event {}: {}", id, e));
+                        }
+                    }
+                }
+                Some(_) => report.duplicates += 1,
+                None if self.events.iter().any(|e| exchange::same_content(e, &event)) => {
+                    report.duplicates += 1;
+                }
+                None => {
+                    let name = event.get_name().clone();
+                    match self.create_event(event) {
+                        Ok(_) => report.added += 1,
+                        Err(e) => {
+                            report.failed += 1;
+                            report.problems.push(format!("Failed to add '{}': {}", name, e));
+                        }
+                    }
+                }
+            }
         }
-        self.add_event(event);
+        report
     }
 
     pub fn list_events(&self) {
         if self.events.is_empty() {
             println!("No events schedued!");
         } else {
-            for (_id,event) in self.events.iter().enumerate() {
+            for event in &self.events {
                 event.display();
             }
         }
     }
 
+    /// Events matching `filter` (all of them without one), ordered by
+    /// `sort` with ties broken by id, and cut to `limit` if given.
+    pub fn query(&self, filter: Option<&Filter>, sort: SortKey, limit: Option<usize>) -> Vec<Event> {
+        let mut events: Vec<Event> = self
+            .events
+            .iter()
+            .filter(|e| filter.is_none_or(|f| f.matches(e)))
+            .cloned()
+            .collect();
+        events.sort_by(|a, b| sort.compare(a, b).then_with(|| a.get_id().cmp(b.get_id())));
+        events.truncate(limit.unwrap_or(usize::MAX));
+        events
+    }
+
+    /// Events whose name or note match `query`, best first.
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        search::search(&self.events, query)
+    }
+
     pub fn display_todays_events(&self, today: NaiveDateTime) {
-        let mut found = false;
-        for event in &self.events {
-            if event.get_start_time().date() == today.date() {
-                event.display();
-                found = true;
-            }
-        }
-        if !found {
+        let occurrences = self.occurrences_on(today.date());
+        if occurrences.is_empty() {
             println!("No events found for today!");
         }
+        for occurrence in &occurrences {
+            occurrence.display();
+        }
     }
 
-    pub fn find_event_mut(&mut self, id: usize) -> Option<&mut Event> {
-        self.events.get_mut(id)
+    /// Every occurrence touching `date`, including multi-day events that
+    /// started earlier.
+    pub fn occurrences_on(&self, date: NaiveDate) -> Vec<Occurrence> {
+        let day_start = date.and_hms_opt(0, 0, 0).unwrap();
+        self.occurrences_between(day_start, day_start + Days::new(1))
     }
 
-    pub fn load_events_file(&self) -> io::Result<Vec<Event>> {
-        let file = File::open(&self.file_name)?;
-        let reader = BufReader::new(file);
-        let mut events = Vec::new();
-        for line in reader.lines() {
-            let line = line?;
-            if let Some(event) = Event::from_string(&line) {
-                events.push(event);
+    /// Occurrences from `first` to `last` (both included) grouped by day.
+    /// Incomplete occurrences that ended before `now`, within the range or
+    /// the last `OVERDUE_LOOKBACK_DAYS` days, are overdue: they leave their
+    /// day and each event lists only its most recent one as overdue.
+    pub fn agenda(&self, first: NaiveDate, last: NaiveDate, now: NaiveDateTime) -> Agenda {
+        let start = first.and_hms_opt(0, 0, 0).unwrap();
+        let is_overdue = |o: &Occurrence| {
+            // All-day occurrences are due by the end of their last date
+            let due = if o.event.is_all_day() { o.end + Days::new(1) } else { o.end };
+            !o.completed && due <= now
+        };
+
+        let lookback = start.min(now - Days::new(OVERDUE_LOOKBACK_DAYS));
+        // Sorted by start, so a later miss replaces an earlier one
+        let mut overdue: Vec<Occurrence> = Vec::new();
+        for occurrence in self.occurrences_between(lookback, now).into_iter().filter(is_overdue) {
+            match overdue.iter_mut().find(|o| o.event.get_id() == occurrence.event.get_id()) {
+                Some(earlier) => *earlier = occurrence,
+                None => overdue.push(occurrence),
             }
         }
-        Ok(events)
+        overdue.sort_by_key(|o| o.start);
+        let mut days = self.occurrences_by_day(first, last);
+        for (_, occurrences) in &mut days {
+            occurrences.retain(|o| !is_overdue(o));
+        }
+        Agenda { overdue, days }
     }
-    
-    pub fn append_event_to_file(&self, event: &Event) -> io::Result<()> {
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.file_name)?;
-        writeln!(file, "{}", event.to_string())?;
-        Ok(())
+
+    /// Every day from `first` to `last` (both included) with the occurrences
+    /// touching it, sorted by start time. Multi-day occurrences appear on
+    /// every day they cover.
+    pub fn occurrences_by_day(&self, first: NaiveDate, last: NaiveDate) -> Vec<(NaiveDate, Vec<Occurrence>)> {
+        let start = first.and_hms_opt(0, 0, 0).unwrap();
+        let end = (last + Days::new(1)).and_hms_opt(0, 0, 0).unwrap();
+        let occurrences = self.occurrences_between(start, end);
+        first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .map(|date| {
+                let day_start = date.and_hms_opt(0, 0, 0).unwrap();
+                let day_end = day_start + Days::new(1);
+                let on_day = occurrences
+                    .iter()
+                    .filter(|o| o.event.spans(o.start, o.end, day_start, day_end))
+                    .cloned()
+                    .collect();
+                (date, on_day)
+            })
+            .collect()
     }
-    
-    pub fn delete_event_in_file(&self, event_id: usize) -> io::Result<()> {
-        let events = self.load_events_file()?;
-        let filtered: Vec<String> = events
+
+    /// Every occurrence overlapping `[start, end)`, sorted by start time.
+    /// One-off events come from `events_between`; recurring events are
+    /// expanded from memory since they may have started long before `start`.
+    pub fn occurrences_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> Vec<Occurrence> {
+        let mut occurrences: Vec<Occurrence> = self
+            .events_between(start, end)
             .into_iter()
-            .filter(|e| *e.get_id() != event_id)
-            .map(|e| e.to_string())
+            .filter(|e| !e.is_recurring())
+            .flat_map(|e| occurrence::expand(&e, start, end))
             .collect();
-        let mut file = File::create(&self.file_name)?;
-        for line in filtered {
-            writeln!(file, "{}", line)?;
+        for event in self.events.iter().filter(|e| e.is_recurring()) {
+            occurrences.extend(occurrence::expand(event, start, end));
         }
-        Ok(())
+        occurrences.sort_by_key(|o| o.start);
+        occurrences
     }
-    
-    pub fn save_events_to_file(&self) -> io::Result<()> {
-        let mut file = File::create(&self.file_name)?;
-        for event in &self.events {
-            writeln!(file, "{}", event.to_string())?;
+
+    /// Events overlapping `[start, end)` of the viewer's zone, sorted by
+    /// start time. Uses the storage backend's index when it has one; the
+    /// query is padded by a day since zoned events are indexed by UTC.
+    pub fn events_between(&self, start: NaiveDateTime, end: NaiveDateTime) -> Vec<Event> {
+        let candidates = match self.storage.events_between(start - Days::new(1), end + Days::new(1)) {
+            Ok(Some(events)) => events,
+            Ok(None) => self.events.clone(),
+            Err(e) => {
+                eprintln!("Failed to query storage, scanning memory instead: {}", e);
+                self.events.clone()
+            }
+        };
+        let mut events: Vec<Event> = candidates
+            .into_iter()
+            .filter(|e| e.spans(e.local_start(), e.local_end(), start, end))
+            .collect();
+        events.sort_by_key(Event::local_start);
+        events
+    }
+
+    pub fn find_event_mut(&mut self, id: usize) -> Option<&mut Event> {
+        let position = *self.index.get(&id)?;
+        self.events.get_mut(position)
+    }
+
+    /// Upgrades the planner file to the current format, backing it up first.
+    /// `None` when it already is; with `dry_run` nothing is written.
+    pub fn migrate(&mut self, dry_run: bool) -> io::Result<Option<MigrationReport>> {
+        if !dry_run && self.storage.version()? < migration::CURRENT_VERSION {
+            self.back_up_before_change()?;
+        }
+        migration::migrate(self.storage.as_mut(), dry_run)
+    }
+
+    /// Replaces the in-memory events with whatever storage holds, upgrading
+    /// files written in an older format first. Ids that collide (from
+    /// planners saved before ids were stable) are reassigned, legacy events
+    /// without a uid get one, and the result is written back. Returns the
+    /// number of events loaded.
+    pub fn load_events(&mut self) -> io::Result<usize> {
+        // On stderr, so it doesn't end up in machine-readable output
+        if let Some(report) = self.migrate(false)? {
+            eprintln!("{}", report);
+        }
+        let mut events = self.storage.load()?;
+        self.events.clear();
+        self.index.clear();
+        self.next_event_id = 0;
+        self.event_count = 0;
+        self.unsaved = false;
+
+        let mut reassigned = false;
+        for event in events.iter_mut().filter(|e| e.get_uid().is_nil()) {
+            event.set_uid(Uuid::new_v4());
+            reassigned = true;
+        }
+        for event in events {
+            let original_id = *event.get_id();
+            reassigned |= self.add_event(event) != original_id;
         }
+        if reassigned {
+            self.save_events()?;
+        }
+        Ok(self.events.len())
+    }
+
+    pub fn update_event(&mut self, id: usize) -> io::Result<()> {
+        match self.index.get(&id) {
+            Some(&position) => {
+                let result = self
+                    .back_up_before_change()
+                    .and_then(|_| self.storage.update(&self.events[position]));
+                self.unsaved |= result.is_err();
+                result
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no event with id {}", id),
+            )),
+        }
+    }
+
+    /// Writes `event` over the one with the same id, then swaps it into the
+    /// planner. When storage fails the planner keeps the old event.
+    pub fn replace_event(&mut self, event: Event) -> io::Result<()> {
+        let Some(&position) = self.index.get(event.get_id()) else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no event with id {}", event.get_id()),
+            ));
+        };
+        self.back_up_before_change()?;
+        self.storage.update(&event)?;
+        self.events[position] = event;
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn save_events(&mut self) -> io::Result<()> {
+        self.back_up_before_change()?;
+        self.storage.flush(&self.events)?;
+        self.unsaved = false;
+        Ok(())
+    }
+
+    /// Whether an edit made in memory couldn't be written to storage, so the
+    /// planner still needs a full save.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Copies every event into a new backend next to the current planner file
+    /// and switches the planner to it.
+    pub fn convert_storage(&mut self, format: StorageFormat) -> io::Result<()> {
+        let file_name = Path::new(self.storage.file_name()).with_extension(format.extension());
+        let file_name = file_name.to_string_lossy().into_owned();
+        let mut new_storage = storage::open_storage(&self.name, file_name, format)?;
+        new_storage.flush(&self.events)?;
+        storage::retire_other_formats(new_storage.file_name())?;
+        self.storage = new_storage;
+        Ok(())
+    }
+
+    pub fn list_backups(&self) -> io::Result<Vec<PathBuf>> {
+        backup::list_backups(self.storage.file_name())
+    }
+
+    /// Replaces the planner file with `backup` and reloads every event from it.
+    pub fn restore_backup(&mut self, backup: &Path) -> io::Result<()> {
+        let file_name = self.storage.file_name().to_string();
+        // Takes its own backup of the current contents
+        backup::restore_backup(&file_name, backup)?;
+        self.backed_up = true;
+        let format = storage::detect_format(&file_name)?;
+        self.storage = storage::open_storage(&self.name, file_name, format)?;
+        self.load_events()?;
+        Ok(())
+    }
+}
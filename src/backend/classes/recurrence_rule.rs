@@ -0,0 +1,537 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backend::enums::frequency::Frequency;
+
+const UNTIL_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+/// Periods in a row that may produce no occurrence before expansion gives
+/// up, so a rule that can never match (e.g. BYMONTHDAY=30 on a Feb-only
+/// yearly rule) doesn't loop forever.
+const MAX_EMPTY_PERIODS: u32 = 1000;
+
+/// A `BYDAY` entry such as `TU`, `2TU` (second Tuesday) or `-1FR` (last
+/// Friday). The ordinal only means something for monthly and yearly rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByDay {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// Subset of an RFC 5545 RRULE: FREQ, INTERVAL, BYDAY, BYMONTHDAY, COUNT and
+/// UNTIL. Yearly rules repeat within the month of the event's start, since
+/// BYMONTH isn't supported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<ByDay>,
+    pub by_month_day: Vec<i32>,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+}
+
+impl RecurrenceRule {
+    pub fn new(frequency: Frequency, interval: u32) -> Self {
+        Self {
+            frequency,
+            interval: interval.max(1),
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            count: None,
+            until: None,
+        }
+    }
+
+    /// Start times of every occurrence in `[from, to)` for an event first
+    /// starting at `dtstart`. `dtstart` itself always counts as the first
+    /// occurrence, matching RFC 5545.
+    ///
+    /// Without BYDAY or BYMONTHDAY, monthly and yearly rules clamp to the
+    /// end of short months (Jan 31 gives Feb 28/29, Feb 29 gives Feb 28)
+    /// instead of skipping them.
+    pub fn starts_between(
+        &self,
+        dtstart: NaiveDateTime,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Vec<NaiveDateTime> {
+        let mut starts = Vec::new();
+        if dtstart >= to
+            || self.until.is_some_and(|until| dtstart > until)
+            || self.count == Some(0)
+        {
+            return starts;
+        }
+        if dtstart >= from {
+            starts.push(dtstart);
+        }
+        let mut seen = 1u32;
+
+        // COUNT has to be tallied from the very first occurrence
+        let mut period = if self.count.is_none() {
+            self.first_period(dtstart, from)
+        } else {
+            0
+        };
+        let mut empty_periods = 0;
+
+        while let Some(candidates) = self.period_candidates(dtstart, period) {
+            let mut produced = false;
+            for candidate in candidates {
+                if candidate <= dtstart {
+                    continue;
+                }
+                if candidate >= to
+                    || self.until.is_some_and(|until| candidate > until)
+                    || self.count.is_some_and(|count| seen >= count)
+                {
+                    return starts;
+                }
+                produced = true;
+                seen += 1;
+                if candidate >= from {
+                    starts.push(candidate);
+                }
+            }
+            empty_periods = if produced { 0 } else { empty_periods + 1 };
+            if empty_periods > MAX_EMPTY_PERIODS {
+                break;
+            }
+            period += 1;
+        }
+        starts
+    }
+
+    /// A period index that begins no later than `from`.
+    fn first_period(&self, dtstart: NaiveDateTime, from: NaiveDateTime) -> u32 {
+        if from <= dtstart {
+            return 0;
+        }
+        let days = (from - dtstart).num_days();
+        let months = (from.year() - dtstart.year()) as i64 * 12 + from.month() as i64
+            - dtstart.month() as i64;
+        let units = match self.frequency {
+            Frequency::Daily => days,
+            Frequency::Weekly => days / 7,
+            Frequency::Monthly => months,
+            Frequency::Yearly => months / 12,
+        };
+        // Step back one in case the estimate overshot by a partial period
+        (units / self.interval as i64).saturating_sub(1).max(0) as u32
+    }
+
+    /// Candidate start times in the `period`th repetition, sorted. `None`
+    /// once dates run past what chrono can represent.
+    fn period_candidates(&self, dtstart: NaiveDateTime, period: u32) -> Option<Vec<NaiveDateTime>> {
+        let step = self.interval.checked_mul(period)?;
+        let time = dtstart.time();
+        let date = dtstart.date();
+
+        let mut dates = match self.frequency {
+            Frequency::Daily => {
+                let day = date.checked_add_days(Days::new(step as u64))?;
+                vec![day].into_iter().filter(|d| self.matches_filters(*d)).collect()
+            }
+            Frequency::Weekly => {
+                let monday = Days::new(date.weekday().num_days_from_monday() as u64);
+                let week_start = date
+                    .checked_sub_days(monday)?
+                    .checked_add_days(Days::new(7 * step as u64))?;
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![date.weekday()]
+                } else {
+                    self.by_day.iter().map(|b| b.weekday).collect()
+                };
+                let mut days: Vec<NaiveDate> = weekdays
+                    .into_iter()
+                    .filter_map(|w| week_start.checked_add_days(Days::new(w.num_days_from_monday() as u64)))
+                    .filter(|d| self.by_month_day.is_empty() || self.matches_month_day(*d))
+                    .collect();
+                days.sort();
+                days
+            }
+            Frequency::Monthly => {
+                let month = date.with_day(1)?.checked_add_months(Months::new(step))?;
+                self.month_candidates(month, date.day())
+            }
+            Frequency::Yearly => {
+                let month = date.with_day(1)?.checked_add_months(Months::new(step.checked_mul(12)?))?;
+                self.month_candidates(month, date.day())
+            }
+        };
+        dates.dedup();
+        Some(dates.into_iter().map(|d| d.and_time(time)).collect())
+    }
+
+    /// Days within the month starting at `first` that the rule selects.
+    fn month_candidates(&self, first: NaiveDate, default_day: u32) -> Vec<NaiveDate> {
+        let last_day = last_day_of_month(first);
+        if self.by_day.is_empty() && self.by_month_day.is_empty() {
+            let day = default_day.min(last_day);
+            return first.with_day(day).into_iter().collect();
+        }
+
+        let mut days: Vec<NaiveDate> = (1..=last_day)
+            .filter_map(|d| first.with_day(d))
+            .filter(|d| self.by_month_day.is_empty() || self.matches_month_day(*d))
+            .filter(|d| self.by_day.is_empty() || self.by_day.iter().any(|b| by_day_matches(b, *d, last_day)))
+            .collect();
+        days.sort();
+        days
+    }
+
+    fn matches_filters(&self, date: NaiveDate) -> bool {
+        (self.by_day.is_empty() || self.by_day.iter().any(|b| b.weekday == date.weekday()))
+            && (self.by_month_day.is_empty() || self.matches_month_day(date))
+    }
+
+    fn matches_month_day(&self, date: NaiveDate) -> bool {
+        let last_day = last_day_of_month(date) as i32;
+        self.by_month_day.iter().any(|&d| {
+            let day = if d < 0 { last_day + d + 1 } else { d };
+            day == date.day() as i32
+        })
+    }
+}
+
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap();
+    let next = first.checked_add_months(Months::new(1)).unwrap();
+    next.pred_opt().unwrap().day()
+}
+
+/// Whether `date` is the weekday (and, with an ordinal, the nth or nth-last
+/// such weekday) named by `by_day` within its month.
+fn by_day_matches(by_day: &ByDay, date: NaiveDate, last_day: u32) -> bool {
+    if date.weekday() != by_day.weekday {
+        return false;
+    }
+    match by_day.ordinal {
+        None => true,
+        Some(n) if n > 0 => (date.day() - 1) / 7 + 1 == n as u32,
+        Some(n) => (last_day - date.day()) / 7 + 1 == n.unsigned_abs(),
+    }
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl fmt::Display for ByDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ordinal) = self.ordinal {
+            write!(f, "{}", ordinal)?;
+        }
+        write!(f, "{}", weekday_code(self.weekday))
+    }
+}
+
+impl FromStr for ByDay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_uppercase();
+        if s.len() < 2 || !s.is_char_boundary(s.len() - 2) {
+            return Err(format!("invalid BYDAY '{}'", s));
+        }
+        let (ordinal, code) = s.split_at(s.len() - 2);
+        let weekday = match code {
+            "MO" => Weekday::Mon,
+            "TU" => Weekday::Tue,
+            "WE" => Weekday::Wed,
+            "TH" => Weekday::Thu,
+            "FR" => Weekday::Fri,
+            "SA" => Weekday::Sat,
+            "SU" => Weekday::Sun,
+            _ => return Err(format!("invalid weekday in BYDAY '{}'", s)),
+        };
+        let ordinal = match ordinal {
+            "" => None,
+            n => match n.trim_start_matches('+').parse::<i32>() {
+                Ok(n) if n != 0 && (-5..=5).contains(&n) => Some(n),
+                _ => return Err(format!("invalid ordinal in BYDAY '{}'", s)),
+            },
+        };
+        Ok(ByDay { ordinal, weekday })
+    }
+}
+
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FREQ={}", self.frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<String> = self.by_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(|d| d.to_string()).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format(UNTIL_FORMAT))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    /// Parses `FREQ=...;INTERVAL=...` with or without a leading `RRULE:`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let body = s
+            .strip_prefix("RRULE:")
+            .or_else(|| s.strip_prefix("rrule:"))
+            .unwrap_or(s);
+
+        let mut frequency = None;
+        let mut rule = RecurrenceRule::new(Frequency::Daily, 1);
+        for part in body.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", part))?;
+            let value = value.trim();
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => frequency = Some(value.parse::<Frequency>()?),
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid INTERVAL '{}'", value))?;
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|d| d.parse::<ByDay>())
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|d| match d.trim().parse::<i32>() {
+                            Ok(n) if n != 0 && (-31..=31).contains(&n) => Ok(n),
+                            _ => Err(format!("invalid BYMONTHDAY '{}'", d)),
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                }
+                "COUNT" => {
+                    rule.count = Some(
+                        value
+                            .parse::<u32>()
+                            .map_err(|_| format!("invalid COUNT '{}'", value))?,
+                    );
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                other => return Err(format!("unsupported RRULE part '{}'", other)),
+            }
+        }
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err("COUNT and UNTIL can't both be set".to_string());
+        }
+        rule.frequency = frequency.ok_or("RRULE is missing FREQ")?;
+        Ok(rule)
+    }
+}
+
+/// Accepts `20261231T235959`, with an optional trailing `Z`, or a bare
+/// `20261231` meaning the end of that day.
+fn parse_until(value: &str) -> Result<NaiveDateTime, String> {
+    let value = value.trim_end_matches('Z');
+    if let Ok(until) = NaiveDateTime::parse_from_str(value, UNTIL_FORMAT) {
+        return Ok(until);
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+        .ok_or_else(|| format!("invalid UNTIL '{}'", value))
+}
+
+impl From<RecurrenceRule> for String {
+    fn from(rule: RecurrenceRule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl TryFrom<String> for RecurrenceRule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// Occurrence dates of `rule` for an event first starting at `dtstart`,
+    /// up to (not including) `to`.
+    fn dates(rule: &str, dtstart: &str, to: &str) -> Vec<String> {
+        let rule: RecurrenceRule = rule.parse().unwrap();
+        rule.starts_between(at(dtstart), at(dtstart), at(to))
+            .iter()
+            .map(|start| start.format("%Y-%m-%d").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn weekly_by_day_lists_each_day_in_order() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;BYDAY=FR,TU", "2026-10-06 09:00", "2026-10-20 00:00"),
+            ["2026-10-06", "2026-10-09", "2026-10-13", "2026-10-16"]
+        );
+    }
+
+    #[test]
+    fn weekly_interval_skips_weeks() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO", "2026-10-05 09:00", "2026-11-10 00:00"),
+            ["2026-10-05", "2026-10-19", "2026-11-02"]
+        );
+    }
+
+    #[test]
+    fn monthly_by_day_ordinals() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=2TU", "2026-10-13 18:00", "2027-01-01 00:00"),
+            ["2026-10-13", "2026-11-10", "2026-12-08"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYDAY=-1FR", "2026-10-30 18:00", "2027-01-01 00:00"),
+            ["2026-10-30", "2026-11-27", "2026-12-25"]
+        );
+    }
+
+    #[test]
+    fn negative_by_month_day_counts_from_month_end() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=-1", "2027-01-31 12:00", "2027-05-01 00:00"),
+            ["2027-01-31", "2027-02-28", "2027-03-31", "2027-04-30"]
+        );
+    }
+
+    #[test]
+    fn by_month_day_skips_months_without_that_day() {
+        assert_eq!(
+            dates("FREQ=MONTHLY;BYMONTHDAY=31", "2027-01-31 12:00", "2027-06-01 00:00"),
+            ["2027-01-31", "2027-03-31", "2027-05-31"]
+        );
+    }
+
+    #[test]
+    fn count_includes_the_first_occurrence() {
+        assert_eq!(
+            dates("FREQ=DAILY;COUNT=3", "2026-10-18 08:00", "2027-01-01 00:00"),
+            ["2026-10-18", "2026-10-19", "2026-10-20"]
+        );
+    }
+
+    #[test]
+    fn count_is_tallied_before_the_window() {
+        let rule: RecurrenceRule = "FREQ=DAILY;COUNT=5".parse().unwrap();
+        let (dtstart, from, to) = (at("2026-10-18 08:00"), at("2026-10-21 00:00"), at("2026-11-01 00:00"));
+        let starts = rule.starts_between(dtstart, from, to);
+        assert_eq!(starts, [at("2026-10-21 08:00"), at("2026-10-22 08:00")]);
+    }
+
+    #[test]
+    fn until_is_inclusive() {
+        assert_eq!(
+            dates("FREQ=WEEKLY;UNTIL=20261101T080000", "2026-10-18 08:00", "2027-01-01 00:00"),
+            ["2026-10-18", "2026-10-25", "2026-11-01"]
+        );
+        assert_eq!(
+            dates("FREQ=WEEKLY;UNTIL=20261101T075959", "2026-10-18 08:00", "2027-01-01 00:00"),
+            ["2026-10-18", "2026-10-25"]
+        );
+    }
+
+    #[test]
+    fn bare_until_date_covers_the_whole_day() {
+        assert_eq!(
+            dates("FREQ=DAILY;UNTIL=20261020", "2026-10-18 23:00", "2027-01-01 00:00"),
+            ["2026-10-18", "2026-10-19", "2026-10-20"]
+        );
+    }
+
+    #[test]
+    fn monthly_clamps_to_the_end_of_short_months() {
+        assert_eq!(
+            dates("FREQ=MONTHLY", "2027-01-31 09:00", "2027-05-01 00:00"),
+            ["2027-01-31", "2027-02-28", "2027-03-31", "2027-04-30"]
+        );
+        assert_eq!(
+            dates("FREQ=MONTHLY", "2028-01-31 09:00", "2028-03-01 00:00"),
+            ["2028-01-31", "2028-02-29"]
+        );
+    }
+
+    #[test]
+    fn yearly_from_leap_day_clamps_to_february_28() {
+        assert_eq!(
+            dates("FREQ=YEARLY", "2028-02-29 09:00", "2033-01-01 00:00"),
+            ["2028-02-29", "2029-02-28", "2030-02-28", "2031-02-28", "2032-02-29"]
+        );
+    }
+
+    #[test]
+    fn window_start_skips_earlier_occurrences() {
+        let rule: RecurrenceRule = "FREQ=DAILY".parse().unwrap();
+        let (dtstart, from, to) = (at("2026-01-01 10:00"), at("2026-10-18 00:00"), at("2026-10-20 00:00"));
+        let starts = rule.starts_between(dtstart, from, to);
+        assert_eq!(starts, [at("2026-10-18 10:00"), at("2026-10-19 10:00")]);
+    }
+
+    #[test]
+    fn rule_that_never_matches_ends() {
+        assert_eq!(
+            dates("FREQ=YEARLY;BYMONTHDAY=30", "2027-02-01 09:00", "2100-01-01 00:00"),
+            ["2027-02-01"]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "FREQ=MONTHLY;INTERVAL=2;BYDAY=1MO,-1FR;BYMONTHDAY=1,-1;UNTIL=20271231T235959";
+        let rule: RecurrenceRule = format!("RRULE:{}", text).parse().unwrap();
+        assert_eq!(rule.to_string(), text);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        for rule in [
+            "INTERVAL=2",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261231",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=MONTHLY;BYDAY=6MO",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=YEARLY;BYMONTH=3",
+        ] {
+            assert!(rule.parse::<RecurrenceRule>().is_err(), "{} should be rejected", rule);
+        }
+    }
+}
@@ -0,0 +1,303 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use chrono_tz::Tz;
+
+use crate::backend::time_zone;
+
+/// A time typed by the user: either a full date-time or a bare date.
+pub enum TimeInput {
+    At(NaiveDateTime),
+    Date(NaiveDate),
+}
+
+const DATE_TIME_FORMATS: [&str; 5] = [
+    "%m-%d-%Y %H:%M",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S",
+];
+const DATE_FORMATS: [&str; 2] = ["%m-%d-%Y", "%Y-%m-%d"];
+
+/// Parses a date and/or time relative to `now`, the current wall-clock time
+/// in `zone` (or in the system zone for floating times). Understands:
+///
+/// - `MM-DD-YYYY HH:MM`, `MM-DD-YYYY` and ISO 8601 (`2026-03-14`,
+///   `2026-03-14T15:00`, `2026-03-14T15:00:00+01:00`)
+/// - `now`, `in 2 hours`, `+45m`
+/// - a day (`today`, `tomorrow`, `yesterday`, `fri`, `next friday`) and/or a
+///   time (`3pm`, `9:30`, `15:00`, `noon`, `midnight`) in either order
+///
+/// A weekday means the next such day, today included; `next` skips today.
+/// A day without a time gives `TimeInput::Date`, a time alone means today.
+pub fn parse(input: &str, now: NaiveDateTime, zone: Option<Tz>) -> Result<TimeInput, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("No date or time entered".to_string());
+    }
+    for format in DATE_TIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(input, format) {
+            return Ok(TimeInput::At(dt));
+        }
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        let utc = dt.naive_utc();
+        return Ok(TimeInput::At(match zone {
+            Some(tz) => time_zone::utc_to_wall(tz, utc),
+            None => time_zone::utc_to_local(utc),
+        }));
+    }
+
+    let lowered = input.to_lowercase().replace(',', " ");
+    let mut words: Vec<String> = Vec::new();
+    for word in lowered.split_whitespace() {
+        match (word, words.last_mut()) {
+            // "3 pm" is the same as "3pm"
+            ("am" | "pm", Some(last)) => last.push_str(word),
+            ("at" | "on", _) => {}
+            _ => words.push(word.to_string()),
+        }
+    }
+    let tokens: Vec<&str> = words.iter().map(String::as_str).collect();
+
+    match tokens.as_slice() {
+        ["now"] => return Ok(TimeInput::At(now)),
+        ["in", rest @ ..] => return after(now, parse_duration(&rest.concat())?).map(TimeInput::At),
+        [offset] if offset.starts_with('+') => {
+            return after(now, parse_duration(&offset[1..])?).map(TimeInput::At);
+        }
+        _ => {}
+    }
+
+    let today = now.date();
+    let mut date = None;
+    let mut time = None;
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        let (parsed_date, parsed_time) = if token == "next" {
+            let Some(weekday) = tokens.next().and_then(|t| t.parse::<Weekday>().ok()) else {
+                return Err("Expected a weekday after 'next'".to_string());
+            };
+            (Some(next_weekday(today + Days::new(1), weekday)), None)
+        } else if let Some(day) = parse_day(token, today) {
+            (Some(day), None)
+        } else if let Some(t) = parse_time_of_day(token) {
+            (None, Some(t))
+        } else {
+            return Err(format!("Didn't understand '{}'", token));
+        };
+        if (parsed_date.is_some() && date.is_some()) || (parsed_time.is_some() && time.is_some()) {
+            return Err(format!("'{}' repeats part of the date or time", token));
+        }
+        date = date.or(parsed_date);
+        time = time.or(parsed_time);
+    }
+
+    match (date, time) {
+        (Some(date), None) => Ok(TimeInput::Date(date)),
+        (date, Some(time)) => Ok(TimeInput::At(date.unwrap_or(today).and_time(time))),
+        (None, None) => Err("No date or time entered".to_string()),
+    }
+}
+
+/// Like `parse` but also takes a duration after `start`, such as `+45m` or
+/// `+1h30m`, for entering when something ends.
+pub fn parse_end(
+    input: &str,
+    start: NaiveDateTime,
+    now: NaiveDateTime,
+    zone: Option<Tz>,
+) -> Result<TimeInput, String> {
+    match input.trim().strip_prefix('+') {
+        Some(duration) => after(start, parse_duration(duration)?).map(TimeInput::At),
+        None => parse(input, now, zone),
+    }
+}
+
+/// Parses a month as `YYYY-MM`, `MM-YYYY`, `this month`, `next month`,
+/// `last month` or any date `parse` accepts. Returns its first day.
+pub fn parse_month(input: &str, now: NaiveDateTime, zone: Option<Tz>) -> Result<NaiveDate, String> {
+    let input = input.trim();
+    let today = now.date();
+    let date = match input.to_lowercase().as_str() {
+        "month" | "this month" => today,
+        "next month" => today + Months::new(1),
+        "last month" => today - Months::new(1),
+        _ => match NaiveDate::parse_from_str(&format!("{}-01", input), "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(&format!("01-{}", input), "%d-%m-%Y"))
+        {
+            Ok(date) => date,
+            Err(_) => match parse(input, now, zone)? {
+                TimeInput::Date(date) => date,
+                TimeInput::At(dt) => dt.date(),
+            },
+        },
+    };
+    Ok(date.with_day(1).unwrap())
+}
+
+/// `at` plus `duration`, or an error when that is past the dates chrono can
+/// represent.
+fn after(at: NaiveDateTime, duration: TimeDelta) -> Result<NaiveDateTime, String> {
+    at.checked_add_signed(duration).ok_or_else(|| "Date out of range".to_string())
+}
+
+/// Parses runs of `<number><unit>` such as `45m`, `2hours` or `1h30m`.
+fn parse_duration(input: &str) -> Result<TimeDelta, String> {
+    let invalid = || format!("Didn't understand the duration '{}'", input);
+    let out_of_range = || "Date out of range".to_string();
+    let mut total = TimeDelta::zero();
+    let mut rest = input;
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let amount: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let letters = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+        let unit_seconds: i64 = match &rest[..letters] {
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "w" | "wk" | "week" | "weeks" => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        rest = &rest[letters..];
+        let seconds = amount.checked_mul(unit_seconds).ok_or_else(out_of_range)?;
+        let step = TimeDelta::try_seconds(seconds).ok_or_else(out_of_range)?;
+        total = total.checked_add(&step).ok_or_else(out_of_range)?;
+    }
+    Ok(total)
+}
+
+fn parse_day(token: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match token {
+        "today" => Some(today),
+        "tomorrow" => Some(today + Days::new(1)),
+        "yesterday" => Some(today - Days::new(1)),
+        _ => token
+            .parse::<Weekday>()
+            .ok()
+            .map(|weekday| next_weekday(today, weekday))
+            .or_else(|| DATE_FORMATS.iter().find_map(|f| NaiveDate::parse_from_str(token, f).ok())),
+    }
+}
+
+/// The first `weekday` on or after `from`.
+fn next_weekday(from: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - from.weekday().num_days_from_monday()) % 7;
+    from + Days::new(ahead as u64)
+}
+
+/// `3pm`, `3:30pm`, `12am`, `9:30`, `15:00`, `noon` or `midnight`.
+fn parse_time_of_day(token: &str) -> Option<NaiveTime> {
+    match token {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+    let (clock, meridiem) = match token.strip_suffix("am") {
+        Some(clock) => (clock, Some(0)),
+        None => match token.strip_suffix("pm") {
+            Some(clock) => (clock, Some(12)),
+            None => (token, None),
+        },
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        // A bare hour needs am/pm, otherwise "9" could be anything
+        None if meridiem.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match meridiem {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A Sunday
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2026-10-18 10:30", "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn parsed_at(input: &str) -> NaiveDateTime {
+        match parse(input, now(), None) {
+            Ok(TimeInput::At(dt)) => dt,
+            Ok(TimeInput::Date(date)) => panic!("{} gave the date {}", input, date),
+            Err(e) => panic!("{} failed: {}", input, e),
+        }
+    }
+
+    fn parsed_date(input: &str) -> NaiveDate {
+        match parse(input, now(), None) {
+            Ok(TimeInput::Date(date)) => date,
+            Ok(TimeInput::At(dt)) => panic!("{} gave the time {}", input, dt),
+            Err(e) => panic!("{} failed: {}", input, e),
+        }
+    }
+
+    #[test]
+    fn absolute_formats() {
+        assert_eq!(parsed_at("03-14-2027 15:00"), at("2027-03-14 15:00"));
+        assert_eq!(parsed_at("2027-03-14T15:00"), at("2027-03-14 15:00"));
+        assert_eq!(parsed_date("2027-03-14"), NaiveDate::from_ymd_opt(2027, 3, 14).unwrap());
+    }
+
+    #[test]
+    fn relative_days_and_times() {
+        assert_eq!(parsed_at("tomorrow 3pm"), at("2026-10-19 15:00"));
+        assert_eq!(parsed_at("3 pm tomorrow"), at("2026-10-19 15:00"));
+        assert_eq!(parsed_at("noon"), at("2026-10-18 12:00"));
+        assert_eq!(parsed_date("yesterday"), NaiveDate::from_ymd_opt(2026, 10, 17).unwrap());
+    }
+
+    #[test]
+    fn weekdays_include_today_unless_next() {
+        assert_eq!(parsed_date("sunday"), NaiveDate::from_ymd_opt(2026, 10, 18).unwrap());
+        assert_eq!(parsed_date("next sunday"), NaiveDate::from_ymd_opt(2026, 10, 25).unwrap());
+        assert_eq!(parsed_at("fri 9:30"), at("2026-10-23 09:30"));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parsed_at("in 2 hours"), at("2026-10-18 12:30"));
+        assert_eq!(parsed_at("+1h30m"), at("2026-10-18 12:00"));
+        assert_eq!(parsed_at("in 1 week"), at("2026-10-25 10:30"));
+        match parse_end("+45m", at("2026-10-20 09:00"), now(), None) {
+            Ok(TimeInput::At(end)) => assert_eq!(end, at("2026-10-20 09:45")),
+            _ => panic!("+45m should be a time"),
+        }
+    }
+
+    #[test]
+    fn durations_past_the_date_range_are_errors() {
+        assert!(parse("in 99999999999 weeks", now(), None).is_err());
+        assert!(parse("+9223372036854775807m", now(), None).is_err());
+        assert!(parse_end("+99999999999999999w", now(), now(), None).is_err());
+    }
+
+    #[test]
+    fn rejects_nonsense() {
+        for input in ["", "9", "13pm", "tomorrow today", "next", "in 5 parsecs"] {
+            assert!(parse(input, now(), None).is_err(), "{} should be rejected", input);
+        }
+    }
+
+    #[test]
+    fn months() {
+        let first = |input| parse_month(input, now(), None).unwrap();
+        assert_eq!(first("2026-11"), NaiveDate::from_ymd_opt(2026, 11, 1).unwrap());
+        assert_eq!(first("11-2026"), NaiveDate::from_ymd_opt(2026, 11, 1).unwrap());
+        assert_eq!(first("next month"), NaiveDate::from_ymd_opt(2026, 11, 1).unwrap());
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+
+/// Which days an agenda covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgendaRange {
+    /// Monday to Sunday of the current week
+    Week,
+    /// Today and the following days, `Days(1)` being just today
+    Days(u32),
+    /// The whole month containing this date
+    Month(NaiveDate),
+    /// From the first date to the second, both included
+    Between(NaiveDate, NaiveDate),
+}
+
+impl AgendaRange {
+    /// First and last day of the range, both included. `None` when the
+    /// range runs past the last date chrono can represent.
+    pub fn dates(&self, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+        match *self {
+            AgendaRange::Week => {
+                let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+                Some((monday, monday.checked_add_days(Days::new(6))?))
+            }
+            AgendaRange::Days(count) => {
+                Some((today, today.checked_add_days(Days::new(count.max(1) as u64 - 1))?))
+            }
+            AgendaRange::Month(date) => {
+                let first = date.with_day(1).unwrap();
+                let next = first.checked_add_months(Months::new(1))?;
+                Some((first, next - Days::new(1)))
+            }
+            AgendaRange::Between(first, last) => Some((first, last)),
+        }
+    }
+}
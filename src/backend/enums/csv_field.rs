@@ -0,0 +1,86 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// An `Event` field as a CSV column. Exports use every field in this order;
+/// imports find them by header name or an explicit mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvField {
+    Id,
+    Uid,
+    Name,
+    Start,
+    End,
+    TimeZone,
+    AllDay,
+    Priority,
+    Recurrence,
+    Note,
+    Completed,
+    Occurrences,
+}
+
+impl CsvField {
+    pub const ALL: [CsvField; 12] = [
+        CsvField::Id,
+        CsvField::Uid,
+        CsvField::Name,
+        CsvField::Start,
+        CsvField::End,
+        CsvField::TimeZone,
+        CsvField::AllDay,
+        CsvField::Priority,
+        CsvField::Recurrence,
+        CsvField::Note,
+        CsvField::Completed,
+        CsvField::Occurrences,
+    ];
+
+    /// Header names, lowercased without spaces or punctuation, that mean
+    /// this field. The first is the one exports write.
+    fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            CsvField::Id => &["id"],
+            CsvField::Uid => &["uid", "uuid"],
+            CsvField::Name => &["name", "title", "summary", "task", "subject", "event"],
+            CsvField::Start => {
+                &["start", "starttime", "startdate", "begin", "date", "due", "duedate", "when"]
+            }
+            CsvField::End => &["end", "endtime", "enddate", "finish"],
+            CsvField::TimeZone => &["timezone", "tz", "zone"],
+            CsvField::AllDay => &["allday"],
+            CsvField::Priority => &["priority", "prio", "importance"],
+            CsvField::Recurrence => &["recurrence", "repeat", "repeats", "reoccurance", "rrule"],
+            CsvField::Note => &["note", "notes", "description", "details", "comment", "comments"],
+            CsvField::Completed => &["completed", "done", "complete", "status", "finished"],
+            CsvField::Occurrences => &["occurrences"],
+        }
+    }
+
+    /// Whether a column headed `header` holds this field.
+    pub fn matches_header(&self, header: &str) -> bool {
+        let key: String = header.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+        self.aliases().contains(&key.as_str())
+    }
+}
+
+impl fmt::Display for CsvField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CsvField::TimeZone => "time_zone",
+            CsvField::AllDay => "all_day",
+            other => other.aliases()[0],
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CsvField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CsvField::ALL
+            .into_iter()
+            .find(|field| field.matches_header(s))
+            .ok_or_else(|| format!("unknown event field '{}'", s.trim()))
+    }
+}
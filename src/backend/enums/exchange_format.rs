@@ -0,0 +1,33 @@
+use std::path::Path;
+use std::str::FromStr;
+
+/// File formats events can be imported from and exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExchangeFormat {
+    ICalendar,
+    Csv,
+    TodoTxt,
+}
+
+impl ExchangeFormat {
+    /// Picks the format from a file's extension.
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        extension
+            .parse()
+            .map_err(|_| format!("can't tell the format of '{}' from its extension, use --type", path))
+    }
+}
+
+impl FromStr for ExchangeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ics" | "ical" | "icalendar" => Ok(ExchangeFormat::ICalendar),
+            "csv" => Ok(ExchangeFormat::Csv),
+            "txt" | "todo" | "todotxt" | "todo.txt" => Ok(ExchangeFormat::TodoTxt),
+            other => Err(format!("unknown file format '{}'", other)),
+        }
+    }
+}
@@ -1,2 +1,10 @@
+pub mod agenda_range;
+pub mod csv_field;
+pub mod exchange_format;
+pub mod frequency;
+pub mod output_format;
 pub mod priority;
-pub mod reoccurance;
\ No newline at end of file
+pub mod reoccurance;
+pub mod report_format;
+pub mod sort_key;
+pub mod storage_format;
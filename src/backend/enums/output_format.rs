@@ -0,0 +1,27 @@
+use std::str::FromStr;
+
+/// How listings are printed: the boxed text view, or a machine-readable
+/// format for piping into other tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    JsonLines,
+    Csv,
+    Tsv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "jsonl" | "jsonlines" | "ndjson" => Ok(OutputFormat::JsonLines),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            other => Err(format!("unknown output format '{}'", other)),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Priority {
@@ -6,3 +7,27 @@ pub enum Priority {
     Medium,
     Low,
 }
+
+impl Priority {
+    /// Higher for more important priorities, for sorting and comparing.
+    pub fn rank(&self) -> u8 {
+        match self {
+            Priority::High => 2,
+            Priority::Medium => 1,
+            Priority::Low => 0,
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "high" => Ok(Priority::High),
+            "medium" => Ok(Priority::Medium),
+            "low" => Ok(Priority::Low),
+            other => Err(format!("unknown priority '{}'", other)),
+        }
+    }
+}
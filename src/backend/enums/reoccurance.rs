@@ -1,4 +1,9 @@
 use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::backend::classes::recurrence_rule::RecurrenceRule;
+use crate::backend::enums::frequency::Frequency;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Reoccurance {
@@ -8,4 +13,64 @@ pub enum Reoccurance {
     Weekly,
     Daily,
     None,
-}
\ No newline at end of file
+    Custom(RecurrenceRule),
+}
+
+impl Reoccurance {
+    /// The RRULE equivalent of this reoccurance, or `None` for one-off events.
+    pub fn to_rule(&self) -> Option<RecurrenceRule> {
+        match self {
+            Reoccurance::Yearly => Some(RecurrenceRule::new(Frequency::Yearly, 1)),
+            Reoccurance::Monthly => Some(RecurrenceRule::new(Frequency::Monthly, 1)),
+            Reoccurance::Fornite => Some(RecurrenceRule::new(Frequency::Weekly, 2)),
+            Reoccurance::Weekly => Some(RecurrenceRule::new(Frequency::Weekly, 1)),
+            Reoccurance::Daily => Some(RecurrenceRule::new(Frequency::Daily, 1)),
+            Reoccurance::None => None,
+            Reoccurance::Custom(rule) => Some(rule.clone()),
+        }
+    }
+
+    /// The named reoccurance `rule` is equivalent to, or a custom one.
+    pub fn from_rule(rule: RecurrenceRule) -> Self {
+        let named = [
+            Reoccurance::Daily,
+            Reoccurance::Weekly,
+            Reoccurance::Fornite,
+            Reoccurance::Monthly,
+            Reoccurance::Yearly,
+        ];
+        named
+            .into_iter()
+            .find(|r| r.to_rule().as_ref() == Some(&rule))
+            .unwrap_or(Reoccurance::Custom(rule))
+    }
+}
+
+impl fmt::Display for Reoccurance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reoccurance::Custom(rule) => write!(f, "RRULE:{}", rule),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+impl FromStr for Reoccurance {
+    type Err = String;
+
+    /// Accepts the named variants or an RRULE such as `FREQ=WEEKLY;BYDAY=TU`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" => Ok(Reoccurance::None),
+            "daily" => Ok(Reoccurance::Daily),
+            "weekly" => Ok(Reoccurance::Weekly),
+            "monthly" => Ok(Reoccurance::Monthly),
+            "yearly" => Ok(Reoccurance::Yearly),
+            "fornite" => Ok(Reoccurance::Fornite),
+            other if other.starts_with("rrule:") || other.contains("freq=") => {
+                s.parse::<RecurrenceRule>().map(Reoccurance::Custom)
+            }
+            other => Err(format!("unknown reoccurance '{}'", other)),
+        }
+    }
+}
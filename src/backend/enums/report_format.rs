@@ -0,0 +1,31 @@
+use std::path::Path;
+use std::str::FromStr;
+
+/// Documents an agenda can be written out as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Picks the format from a file's extension.
+    pub fn from_path(path: &str) -> Result<Self, String> {
+        let extension = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("");
+        extension
+            .parse()
+            .map_err(|_| format!("can't tell the format of '{}' from its extension, use --type", path))
+    }
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "html" | "htm" => Ok(ReportFormat::Html),
+            other => Err(format!("unknown report format '{}'", other)),
+        }
+    }
+}
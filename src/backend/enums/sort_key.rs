@@ -0,0 +1,62 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use crate::backend::classes::event::Event;
+
+/// What to order query results by. Parsed from `start`, `end`, `name`,
+/// `priority` or `id`, with a leading `-` for descending order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Start,
+    End,
+    Name,
+    Priority,
+    Id,
+}
+
+impl SortKey {
+    pub fn compare(&self, a: &Event, b: &Event) -> Ordering {
+        let ordering = match self.field {
+            SortField::Start => a.local_start().cmp(&b.local_start()),
+            SortField::End => a.local_end().cmp(&b.local_end()),
+            SortField::Name => a.get_name().to_lowercase().cmp(&b.get_name().to_lowercase()),
+            // Most important first
+            SortField::Priority => b.get_priority().rank().cmp(&a.get_priority().rank()),
+            SortField::Id => a.get_id().cmp(b.get_id()),
+        };
+        if self.descending { ordering.reverse() } else { ordering }
+    }
+}
+
+impl Default for SortKey {
+    fn default() -> Self {
+        SortKey { field: SortField::Start, descending: false }
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s.as_str()),
+        };
+        let field = match name {
+            "start" => SortField::Start,
+            "end" => SortField::End,
+            "name" => SortField::Name,
+            "priority" => SortField::Priority,
+            "id" => SortField::Id,
+            other => return Err(format!("unknown sort key '{}'", other)),
+        };
+        Ok(SortKey { field, descending })
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageFormat {
+    Text,
+    Json,
+    PrettyJson,
+    Sqlite,
+}
+
+impl StorageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StorageFormat::Text => "txt",
+            StorageFormat::Json | StorageFormat::PrettyJson => "json",
+            StorageFormat::Sqlite => "db",
+        }
+    }
+}
+
+impl FromStr for StorageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(StorageFormat::Text),
+            "json" => Ok(StorageFormat::Json),
+            "prettyjson" | "pretty" => Ok(StorageFormat::PrettyJson),
+            "sqlite" | "db" => Ok(StorageFormat::Sqlite),
+            other => Err(format!("unknown storage format '{}'", other)),
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use ::csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use chrono::{NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::OccurrenceState;
+use crate::backend::date_parser::{self, TimeInput};
+use crate::backend::enums::csv_field::CsvField;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::exchange::{self, Parsed};
+use crate::backend::time_zone;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A column, by header name or by position counting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Column {
+    Header(String),
+    Position(usize),
+}
+
+/// Which column an event field comes from, written `field=column` as in
+/// `start=Due Date` or `name=2`.
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    pub field: CsvField,
+    pub column: Column,
+}
+
+impl FromStr for ColumnMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, column) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected FIELD=COLUMN, got '{}'", s))?;
+        let column = column.trim();
+        let column = match column.parse::<usize>() {
+            Ok(0) => return Err("columns are numbered from 1".to_string()),
+            Ok(position) => Column::Position(position),
+            Err(_) => Column::Header(column.to_string()),
+        };
+        Ok(ColumnMapping { field: field.parse()?, column })
+    }
+}
+
+/// How to read a CSV file into events.
+pub struct CsvOptions {
+    /// Explicit columns; other fields are found by their header names
+    pub mappings: Vec<ColumnMapping>,
+    /// chrono formats such as `%d/%m/%Y %H:%M`, tried in order before the
+    /// usual date parsing
+    pub date_formats: Vec<String>,
+    pub delimiter: u8,
+    pub has_header: bool,
+    /// Zone for times in files without a time zone column
+    pub zone: Option<Tz>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            mappings: Vec::new(),
+            date_formats: Vec::new(),
+            delimiter: b',',
+            has_header: true,
+            zone: time_zone::local_zone(),
+        }
+    }
+}
+
+fn field_value(event: &Event, field: CsvField) -> String {
+    let time = |wall: NaiveDateTime| {
+        let format = if event.is_all_day() { DATE_FORMAT } else { TIME_FORMAT };
+        wall.format(format).to_string()
+    };
+    match field {
+        CsvField::Id => event.get_id().to_string(),
+        CsvField::Uid => event.get_uid().to_string(),
+        CsvField::Name => event.get_name().clone(),
+        CsvField::Start => time(event.get_start_time()),
+        CsvField::End => time(event.get_end_time()),
+        CsvField::TimeZone => event.get_time_zone().map(|tz| tz.name()).unwrap_or("").to_string(),
+        CsvField::AllDay => event.is_all_day().to_string(),
+        CsvField::Priority => format!("{:?}", event.get_priority()),
+        CsvField::Recurrence => event.get_reoccurance().to_string(),
+        CsvField::Note => event.get_note().clone(),
+        CsvField::Completed => event.get_completed().to_string(),
+        CsvField::Occurrences => event.get_occurrences().to_json(),
+    }
+}
+
+/// Writes every field of `events` with a header row. Times are wall-clock
+/// in the event's own zone (given in `time_zone`, empty for floating and
+/// all-day events), and `occurrences` holds per-occurrence changes as JSON.
+pub fn export(events: &[Event], out: &mut impl Write) -> io::Result<()> {
+    let mut writer = WriterBuilder::new().from_writer(out);
+    writer
+        .write_record(CsvField::ALL.iter().map(|f| f.to_string()))
+        .map_err(io::Error::other)?;
+    for event in events {
+        writer
+            .write_record(CsvField::ALL.iter().map(|f| field_value(event, *f)))
+            .map_err(io::Error::other)?;
+    }
+    writer.flush()
+}
+
+fn reader<'a>(text: &'a str, options: &CsvOptions) -> ::csv::Reader<&'a [u8]> {
+    ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+}
+
+/// The first row of the file, the header if it has one.
+pub fn headers(text: &str, options: &CsvOptions) -> Result<Vec<String>, String> {
+    match reader(text, options).records().next() {
+        Some(Ok(row)) => Ok(row.iter().map(|h| h.trim().to_string()).collect()),
+        Some(Err(e)) => Err(format!("couldn't read the first row: {}", e)),
+        None => Err("the file is empty".to_string()),
+    }
+}
+
+/// The column index of each field: explicit mappings first, then headers
+/// named after the field. Fails without a column for the name or start.
+pub fn resolve_columns(headers: &[String], options: &CsvOptions) -> Result<Vec<(CsvField, usize)>, String> {
+    let mut columns = Vec::new();
+    for mapping in &options.mappings {
+        let index = match &mapping.column {
+            Column::Position(position) => position - 1,
+            Column::Header(name) if options.has_header => headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name.trim()))
+                .ok_or_else(|| format!("there's no column named '{}'", name))?,
+            Column::Header(name) => {
+                return Err(format!("the file has no header, so map '{}' to a column number", name));
+            }
+        };
+        columns.retain(|(field, _)| *field != mapping.field);
+        columns.push((mapping.field, index));
+    }
+    if options.has_header {
+        for field in CsvField::ALL {
+            let taken = |index: usize| columns.iter().any(|(f, i)| *f == field || *i == index);
+            if let Some(index) = headers.iter().position(|h| field.matches_header(h))
+                && !taken(index)
+            {
+                columns.push((field, index));
+            }
+        }
+    }
+    for required in [CsvField::Name, CsvField::Start] {
+        if !columns.iter().any(|(field, _)| *field == required) {
+            return Err(format!("no column holds the event's {}, map one with {}=COLUMN", required, required));
+        }
+    }
+    Ok(columns)
+}
+
+fn parse_bool(text: &str) -> Result<bool, String> {
+    match text.trim().to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" | "done" | "complete" | "completed" => Ok(true),
+        "false" | "no" | "n" | "0" | "" | "todo" | "open" | "pending" | "not done" => Ok(false),
+        other => Err(format!("'{}' isn't yes or no", other)),
+    }
+}
+
+fn parse_priority(text: &str) -> Result<Priority, String> {
+    match text.trim().to_lowercase().as_str() {
+        "h" => Ok(Priority::High),
+        "m" => Ok(Priority::Medium),
+        "l" => Ok(Priority::Low),
+        other => other.parse(),
+    }
+}
+
+/// A time in one of the user's formats, or anything the date parser takes.
+fn parse_time(text: &str, options: &CsvOptions, zone: Option<Tz>) -> Result<TimeInput, String> {
+    for format in &options.date_formats {
+        if let Ok(time) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(TimeInput::At(time));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(text, format) {
+            return Ok(TimeInput::Date(date));
+        }
+    }
+    date_parser::parse(text, time_zone::now_in(zone), zone)
+}
+
+fn date_of(time: TimeInput) -> NaiveDate {
+    match time {
+        TimeInput::At(at) => at.date(),
+        TimeInput::Date(date) => date,
+    }
+}
+
+fn build_event(
+    row: &StringRecord,
+    columns: &[(CsvField, usize)],
+    options: &CsvOptions,
+) -> Result<Event, String> {
+    let column = |field: CsvField| columns.iter().find(|(f, _)| *f == field).map(|(_, index)| *index);
+    let get = |field: CsvField| {
+        column(field)
+            .and_then(|index| row.get(index))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    let name = get(CsvField::Name).ok_or("the name is empty")?;
+    let zone = match column(CsvField::TimeZone) {
+        None => options.zone,
+        Some(_) => match get(CsvField::TimeZone) {
+            None => None,
+            Some(zone) if zone.eq_ignore_ascii_case("floating") => None,
+            Some(zone) => Some(zone.parse::<Tz>().map_err(|_| format!("unknown time zone '{}'", zone))?),
+        },
+    };
+    let start_text = get(CsvField::Start).ok_or("the start is empty")?;
+    let start = parse_time(start_text, options, zone).map_err(|e| format!("start '{}': {}", start_text, e))?;
+    let all_day = match get(CsvField::AllDay) {
+        Some(all_day) => parse_bool(all_day).map_err(|e| format!("all day: {}", e))?,
+        None => false,
+    } || matches!(start, TimeInput::Date(_));
+
+    let end_text = get(CsvField::End);
+    let (start_time, end_time) = if all_day {
+        let start_date = date_of(start);
+        let end_date = match end_text {
+            Some(text) => {
+                date_of(parse_time(text, options, zone).map_err(|e| format!("end '{}': {}", text, e))?)
+            }
+            None => start_date,
+        };
+        (start_date.and_hms_opt(0, 0, 0).unwrap(), end_date.and_hms_opt(0, 0, 0).unwrap())
+    } else {
+        let TimeInput::At(start_time) = start else { unreachable!("dates are all-day") };
+        let end_time = match end_text {
+            None => start_time,
+            Some(text) => {
+                let end = match text.strip_prefix('+') {
+                    Some(_) => date_parser::parse_end(text, start_time, start_time, zone),
+                    None => parse_time(text, options, zone),
+                };
+                match end.map_err(|e| format!("end '{}': {}", text, e))? {
+                    TimeInput::At(end) => end,
+                    TimeInput::Date(_) => return Err(format!("end '{}' needs a time like the start", text)),
+                }
+            }
+        };
+        (start_time, end_time)
+    };
+    if end_time < start_time {
+        return Err("the event ends before it starts".to_string());
+    }
+
+    let priority = match get(CsvField::Priority) {
+        Some(priority) => parse_priority(priority)?,
+        None => Priority::Medium,
+    };
+    let reoccurance = match get(CsvField::Recurrence) {
+        Some(recurrence) => recurrence.parse::<Reoccurance>()?,
+        None => Reoccurance::None,
+    };
+    let completed = match get(CsvField::Completed) {
+        Some(completed) => parse_bool(completed).map_err(|e| format!("completed: {}", e))?,
+        None => false,
+    };
+
+    let mut event = Event::new(
+        0,
+        name.to_string(),
+        start_time,
+        end_time,
+        priority,
+        reoccurance,
+        get(CsvField::Note).unwrap_or("").to_string(),
+        completed,
+    );
+    if all_day {
+        event.set_all_day(true);
+    } else {
+        event.set_time_zone(zone);
+    }
+    if let Some(uid) = get(CsvField::Uid) {
+        event.set_uid(exchange::uid_from(uid));
+    }
+    if let Some(occurrences) = get(CsvField::Occurrences) {
+        let state = OccurrenceState::from_json(occurrences).map_err(|e| format!("occurrences: {}", e))?;
+        event.set_occurrences(state);
+    }
+    Ok(event)
+}
+
+/// Reads one event per row. Rows that can't be read are skipped and
+/// reported by line number; blank rows are ignored.
+pub fn import(text: &str, options: &CsvOptions) -> Parsed {
+    let mut parsed = Parsed::default();
+    let headers = if options.has_header {
+        match headers(text, options) {
+            Ok(headers) => headers,
+            Err(e) => {
+                parsed.problems.push(e);
+                return parsed;
+            }
+        }
+    } else {
+        Vec::new()
+    };
+    let columns = match resolve_columns(&headers, options) {
+        Ok(columns) => columns,
+        Err(e) => {
+            parsed.problems.push(e);
+            return parsed;
+        }
+    };
+
+    let mut reader = reader(text, options);
+    let rows = reader.records().skip(if options.has_header { 1 } else { 0 });
+    for (index, row) in rows.enumerate() {
+        let line = match &row {
+            Ok(row) => row.position().map(|p| p.line()),
+            Err(e) => e.position().map(|p| p.line()),
+        }
+        .unwrap_or(index as u64 + 1);
+        match row {
+            Ok(row) if row.iter().all(|value| value.trim().is_empty()) => {}
+            Ok(row) => match build_event(&row, &columns, options) {
+                Ok(event) => parsed.events.push(event),
+                Err(e) => parsed.problems.push(format!("line {}: {}, skipped", line, e)),
+            },
+            Err(e) => parsed.problems.push(format!("line {}: {}, skipped", line, e)),
+        }
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// Floating times, so results don't depend on the system's zone.
+    fn floating() -> CsvOptions {
+        CsvOptions { zone: None, ..CsvOptions::default() }
+    }
+
+    fn mappings(mappings: &[&str]) -> Vec<ColumnMapping> {
+        mappings.iter().map(|mapping| mapping.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn header_aliases_are_recognised() {
+        let text = "Task,Due Date,Done\nBuy milk,2026-10-20 09:00,yes\nFile taxes,2026-10-21,\n";
+        let parsed = import(text, &floating());
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        let [milk, taxes] = &parsed.events[..] else { panic!("{:?}", parsed.events) };
+        assert_eq!(milk.get_name(), "Buy milk");
+        assert_eq!(milk.get_start_time(), at("2026-10-20 09:00"));
+        assert!(milk.get_completed());
+        assert_eq!(taxes.get_name(), "File taxes");
+        assert!(taxes.is_all_day() && !taxes.get_completed());
+    }
+
+    #[test]
+    fn columns_are_mapped_by_name_or_position() {
+        let text = "What,Notes,On\nDentist,bring card,2026-10-20 14:00\n";
+        let options = CsvOptions { mappings: mappings(&["name=what", "start=3"]), ..floating() };
+        let parsed = import(text, &options);
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.events[0].get_name(), "Dentist");
+        assert_eq!(parsed.events[0].get_note(), "bring card");
+        assert_eq!(parsed.events[0].get_start_time(), at("2026-10-20 14:00"));
+
+        // A mapping wins over a header that names another field
+        let text = "Name,Title,Start\nignored,Dentist,2026-10-20 14:00\n";
+        let options = CsvOptions { mappings: mappings(&["name=Title"]), ..options };
+        assert_eq!(import(text, &options).events[0].get_name(), "Dentist");
+
+        assert!("name=0".parse::<ColumnMapping>().is_err());
+        assert!("colour=2".parse::<ColumnMapping>().is_err());
+        let options = CsvOptions { mappings: mappings(&["name=Missing"]), ..floating() };
+        assert!(import("What,When\n", &options).problems[0].contains("no column named 'Missing'"));
+    }
+
+    #[test]
+    fn files_without_a_header_need_positions() {
+        let text = "2026-10-20 14:00,Dentist\n2026-10-21 09:00,Gym\n";
+        let positions = mappings(&["start=1", "name=2"]);
+        let options = CsvOptions { mappings: positions, has_header: false, ..floating() };
+        let parsed = import(text, &options);
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        let names: Vec<&String> = parsed.events.iter().map(|e| e.get_name()).collect();
+        assert_eq!(names, ["Dentist", "Gym"]);
+
+        let options = CsvOptions { mappings: mappings(&["start=1", "name=Title"]), ..options };
+        assert!(import(text, &options).events.is_empty());
+    }
+
+    #[test]
+    fn date_formats_are_tried_first() {
+        let text = "name,start,end\n\
+                    Stand-up,20/10/2026 09:30,20/10/2026 09:45\n\
+                    Holiday,24/12/2026,26/12/2026\n";
+        let formats = vec!["%d/%m/%Y %H:%M".to_string(), "%d/%m/%Y".to_string()];
+        let parsed = import(text, &CsvOptions { date_formats: formats, ..floating() });
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.events[0].get_start_time(), at("2026-10-20 09:30"));
+        assert_eq!(parsed.events[0].get_end_time(), at("2026-10-20 09:45"));
+        assert!(parsed.events[1].is_all_day());
+        assert_eq!(parsed.events[1].get_end_time(), at("2026-12-26 00:00"));
+    }
+
+    #[test]
+    fn bad_rows_are_reported_by_line_and_skipped() {
+        let text = "name,start,note\n\
+                    Good,2026-10-20 09:00,\"spans\ntwo lines\"\n\
+                    Bad,not a date at all,\n\
+                    ,2026-10-20 09:00,\n\
+                    \n\
+                    Also good,2026-10-21 09:00,\n";
+        let parsed = import(text, &floating());
+        let names: Vec<&String> = parsed.events.iter().map(|e| e.get_name()).collect();
+        assert_eq!(names, ["Good", "Also good"]);
+        assert_eq!(parsed.problems.len(), 2, "{:?}", parsed.problems);
+        assert!(parsed.problems[0].starts_with("line 4: start 'not a date at all'"), "{:?}", parsed.problems);
+        assert_eq!(parsed.problems[1], "line 5: the name is empty, skipped");
+    }
+}
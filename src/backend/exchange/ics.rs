@@ -0,0 +1,911 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use chrono::{Datelike, Days, Duration, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::OccurrenceOverride;
+use crate::backend::classes::recurrence_rule::RecurrenceRule;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::exchange::{self, Parsed};
+use crate::backend::time_zone;
+
+const PRODID: &str = "-//to-do//Rust To-Do Planner//EN";
+const DATE_FORMAT: &str = "%Y%m%d";
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+/// Content lines longer than this many bytes are folded (RFC 5545 3.1).
+const LINE_LIMIT: usize = 75;
+/// Non-standard property carrying completion for VEVENTs, whose STATUS has
+/// no completed value.
+const COMPLETED_PROPERTY: &str = "X-TODO-COMPLETED";
+
+/// Writes `events` as an iCalendar file. Zero-length events are tasks and
+/// become VTODOs due at their time; everything else is a VEVENT. Moved,
+/// renamed or completed occurrences of recurring events get their own
+/// component with a RECURRENCE-ID, and skipped ones are listed in EXDATE.
+pub fn export(events: &[Event], out: &mut impl Write) -> io::Result<()> {
+    let stamp = Utc::now().naive_utc().format(DATE_TIME_FORMAT).to_string() + "Z";
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    // Every TZID used needs a VTIMEZONE, covering the years the events use
+    let mut zones: BTreeMap<&str, (Tz, i32, i32)> = BTreeMap::new();
+    let this_year = Utc::now().year();
+    for event in events.iter().filter(|e| !e.is_all_day()) {
+        if let Some(tz) = event.get_time_zone() {
+            let year = event.get_start_time().year();
+            let entry = zones.entry(tz.name()).or_insert((tz, year, this_year + 1));
+            entry.1 = entry.1.min(year);
+            entry.2 = entry.2.max(event.get_end_time().year() + 1);
+        }
+    }
+    for (tz, first_year, last_year) in zones.values() {
+        lines.extend(vtimezone(*tz, *first_year, *last_year));
+    }
+
+    for event in events {
+        lines.extend(components(event, &stamp));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        write_folded(out, &line)?;
+    }
+    Ok(())
+}
+
+fn is_task(event: &Event) -> bool {
+    !event.is_all_day() && event.get_start_time() == event.get_end_time()
+}
+
+/// `NAME;PARAMS:VALUE` for a wall-clock time in the event's zone.
+fn time_property(name: &str, event: &Event, wall: NaiveDateTime) -> String {
+    if event.is_all_day() {
+        return format!("{};VALUE=DATE:{}", name, wall.format(DATE_FORMAT));
+    }
+    match event.get_time_zone() {
+        Some(tz) => format!("{};TZID={}:{}", name, tz.name(), wall.format(DATE_TIME_FORMAT)),
+        None => format!("{}:{}", name, wall.format(DATE_TIME_FORMAT)),
+    }
+}
+
+/// The RRULE, with UNTIL written the way RFC 5545 wants it for the kind of
+/// DTSTART: a date for all-day events, UTC for zoned ones.
+fn rrule(event: &Event) -> Option<String> {
+    let mut rule = event.get_reoccurance().to_rule()?;
+    let until = rule.until.take();
+    let mut text = format!("RRULE:{}", rule);
+    if let Some(until) = until {
+        let until = if event.is_all_day() {
+            until.format(DATE_FORMAT).to_string()
+        } else if let Some(tz) = event.get_time_zone() {
+            time_zone::wall_to_utc(tz, until).format(DATE_TIME_FORMAT).to_string() + "Z"
+        } else {
+            until.format(DATE_TIME_FORMAT).to_string()
+        };
+        text.push_str(&format!(";UNTIL={}", until));
+    }
+    Some(text)
+}
+
+fn priority_number(priority: &Priority) -> u8 {
+    match priority {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn status_lines(kind: &str, completed: bool) -> Vec<String> {
+    if kind == "VTODO" {
+        let status = if completed { "COMPLETED" } else { "NEEDS-ACTION" };
+        vec![format!("STATUS:{}", status)]
+    } else {
+        let mut lines = vec!["STATUS:CONFIRMED".to_string()];
+        if completed {
+            lines.push(format!("{}:TRUE", COMPLETED_PROPERTY));
+        }
+        lines
+    }
+}
+
+/// The event's own component followed by one per changed occurrence.
+fn components(event: &Event, stamp: &str) -> Vec<String> {
+    let kind = if is_task(event) { "VTODO" } else { "VEVENT" };
+    let start = event.get_start_time();
+    let end = event.get_end_time();
+    // All-day DTEND is the day after the last date
+    let end_property = |wall: NaiveDateTime| {
+        if kind == "VTODO" {
+            time_property("DUE", event, wall)
+        } else if event.is_all_day() {
+            time_property("DTEND", event, wall + Days::new(1))
+        } else {
+            time_property("DTEND", event, wall)
+        }
+    };
+    let header = |lines: &mut Vec<String>| {
+        lines.push(format!("BEGIN:{}", kind));
+        lines.push(format!("UID:{}", event.get_uid()));
+        lines.push(format!("DTSTAMP:{}", stamp));
+    };
+
+    let mut lines = Vec::new();
+    header(&mut lines);
+    lines.push(format!("SUMMARY:{}", escape_text(event.get_name())));
+    lines.push(time_property("DTSTART", event, start));
+    lines.push(end_property(end));
+    lines.extend(rrule(event));
+    let state = event.get_occurrences();
+    for skipped in &state.exceptions {
+        lines.push(time_property("EXDATE", event, *skipped));
+    }
+    lines.push(format!("PRIORITY:{}", priority_number(event.get_priority())));
+    lines.extend(status_lines(kind, event.get_completed()));
+    if !event.get_note().is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape_text(event.get_note())));
+    }
+    lines.push(format!("END:{}", kind));
+
+    if !event.is_recurring() {
+        return lines;
+    }
+    let mut changed: Vec<NaiveDateTime> = state.overrides.keys().chain(&state.completed).copied().collect();
+    changed.sort();
+    changed.dedup();
+    for original in changed.into_iter().filter(|s| !state.exceptions.contains(s)) {
+        let moved = state.overrides.get(&original);
+        let name = moved.and_then(|m| m.name.as_ref()).unwrap_or(event.get_name());
+        let (start, end) = match moved {
+            Some(moved) => (moved.start, moved.end),
+            None => (original, original + (end - start)),
+        };
+        header(&mut lines);
+        lines.push(time_property("RECURRENCE-ID", event, original));
+        lines.push(format!("SUMMARY:{}", escape_text(name)));
+        lines.push(time_property("DTSTART", event, start));
+        lines.push(end_property(end));
+        lines.push(format!("PRIORITY:{}", priority_number(event.get_priority())));
+        let completed = event.get_completed() || state.completed.contains(&original);
+        lines.extend(status_lines(kind, completed));
+        lines.push(format!("END:{}", kind));
+    }
+    lines
+}
+
+fn offset_text(seconds: i32) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let seconds = seconds.abs();
+    let mut text = format!("{}{:02}{:02}", sign, seconds / 3600, seconds / 60 % 60);
+    if seconds % 60 != 0 {
+        text.push_str(&format!("{:02}", seconds % 60));
+    }
+    text
+}
+
+/// A VTIMEZONE listing the zone's offset changes from `first_year` through
+/// `last_year`, found by stepping through the years a day at a time.
+fn vtimezone(tz: Tz, first_year: i32, last_year: i32) -> Vec<String> {
+    let offset_at = |utc: NaiveDateTime| tz.offset_from_utc_datetime(&utc);
+    let seconds = |utc: NaiveDateTime| offset_at(utc).fix().local_minus_utc();
+    let observance = |lines: &mut Vec<String>, utc: NaiveDateTime, from: i32| {
+        let offset = offset_at(utc);
+        let kind = if offset.dst_offset().is_zero() { "STANDARD" } else { "DAYLIGHT" };
+        lines.push(format!("BEGIN:{}", kind));
+        // The wall-clock time the change happens at, read on the old clock
+        // Only fails at the very edge of chrono's range, where `utc` is close enough
+        let wall = utc.checked_add_signed(Duration::seconds(from as i64)).unwrap_or(utc);
+        lines.push(format!("DTSTART:{}", wall.format(DATE_TIME_FORMAT)));
+        lines.push(format!("TZOFFSETFROM:{}", offset_text(from)));
+        lines.push(format!("TZOFFSETTO:{}", offset_text(offset.fix().local_minus_utc())));
+        if let Some(name) = offset.abbreviation() {
+            lines.push(format!("TZNAME:{}", name));
+        }
+        lines.push(format!("END:{}", kind));
+    };
+
+    let mut lines = vec!["BEGIN:VTIMEZONE".to_string(), format!("TZID:{}", tz.name())];
+    let mut day = NaiveDate::from_ymd_opt(first_year, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+    // Events in chrono's last year have no next new year to stop at
+    let end = NaiveDate::from_ymd_opt(last_year + 1, 1, 1)
+        .map_or(NaiveDateTime::MAX, |date| date.and_hms_opt(0, 0, 0).unwrap());
+    observance(&mut lines, day, seconds(day));
+    while day < end {
+        let Some(next) = day.checked_add_days(Days::new(1)) else { break };
+        if seconds(next) != seconds(day) {
+            // Narrow the change down to the minute
+            let (mut before, mut after) = (0, 24 * 60);
+            while after - before > 1 {
+                let middle = (before + after) / 2;
+                if seconds(day + Duration::minutes(middle)) == seconds(day) {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            observance(&mut lines, day + Duration::minutes(after), seconds(day));
+        }
+        day = next;
+    }
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Writes `line` with CRLF endings, folding it onto continuation lines that
+/// start with a space so none is longer than `LINE_LIMIT` bytes.
+fn write_folded(out: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut rest = line;
+    let mut limit = LINE_LIMIT;
+    while rest.len() > limit {
+        let mut cut = limit;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        write!(out, "{}\r\n ", &rest[..cut])?;
+        rest = &rest[cut..];
+        // The leading space counts towards the limit
+        limit = LINE_LIMIT - 1;
+    }
+    write!(out, "{}\r\n", rest)
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// A VEVENT or VTODO, with the line it began on for error messages.
+struct Component {
+    kind: String,
+    line: usize,
+    properties: Vec<Property>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(|p| unescape_text(&p.value))
+    }
+
+    fn uid(&self) -> String {
+        self.get("UID").map(|p| p.value.trim().to_string()).unwrap_or_default()
+    }
+
+    fn completed(&self) -> bool {
+        let status = self.get("STATUS").map(|p| p.value.trim().to_uppercase());
+        status.as_deref() == Some("COMPLETED")
+            || self.get(COMPLETED_PROPERTY).is_some_and(|p| p.value.trim().eq_ignore_ascii_case("true"))
+            || self.get("COMPLETED").is_some()
+            || self.get("PERCENT-COMPLETE").is_some_and(|p| p.value.trim() == "100")
+    }
+
+    fn cancelled(&self) -> bool {
+        self.get("STATUS").is_some_and(|p| p.value.trim().eq_ignore_ascii_case("CANCELLED"))
+    }
+
+    fn describe(&self) -> String {
+        match self.text("SUMMARY") {
+            Some(name) => format!("line {}: {} '{}'", self.line, self.kind, name),
+            None => format!("line {}: {}", self.line, self.kind),
+        }
+    }
+}
+
+/// Splits `NAME;PARAM=VALUE;...:VALUE`, where quoted parameter values may
+/// contain `:` and `;`.
+fn parse_line(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut split = None;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                split = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let split = split?;
+    let (head, value) = (&line[..split], &line[split + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    let params = parts
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim().to_uppercase(), value.trim().trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// The VEVENTs and VTODOs in `text`. Nested components such as VALARM are
+/// left out of their parent.
+fn parse_components(text: &str) -> (Vec<Component>, Vec<String>) {
+    // Undo line folding first, remembering where each logical line began
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (number, raw) in text.lines().enumerate() {
+        let raw = raw.trim_end_matches('\r');
+        match (raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continued), Some((_, line))) => line.push_str(continued),
+            _ => lines.push((number + 1, raw.to_string())),
+        }
+    }
+
+    let mut components = Vec::new();
+    let mut problems = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<Component> = None;
+    for (number, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(property) = parse_line(&line) else {
+            problems.push(format!("line {}: not an iCalendar line, ignored", number));
+            continue;
+        };
+        let value = property.value.trim().to_uppercase();
+        match property.name.as_str() {
+            "BEGIN" => {
+                if current.is_none() && (value == "VEVENT" || value == "VTODO") {
+                    current = Some(Component { kind: value.clone(), line: number, properties: Vec::new() });
+                }
+                stack.push(value);
+            }
+            "END" => {
+                if stack.pop().as_deref() != Some(value.as_str()) {
+                    problems.push(format!("line {}: END:{} doesn't match its BEGIN", number, value));
+                }
+                if current.as_ref().is_some_and(|c| c.kind == value && !stack.contains(&value)) {
+                    components.extend(current.take());
+                }
+            }
+            _ => {
+                if let Some(component) = current.as_mut().filter(|c| stack.last() == Some(&c.kind)) {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+    if current.is_some() {
+        problems.push("the file ends inside a component, which was skipped".to_string());
+    }
+    (components, problems)
+}
+
+/// A time value as written, before it is placed in an event's zone.
+#[derive(Clone, Copy)]
+enum IcsTime {
+    Date(NaiveDate),
+    Floating(NaiveDateTime),
+    Utc(NaiveDateTime),
+    Zoned(Tz, NaiveDateTime),
+}
+
+impl IcsTime {
+    /// The wall-clock time in `zone` (the viewer's for floating events).
+    fn wall_in(self, zone: Option<Tz>) -> NaiveDateTime {
+        let utc = match self {
+            IcsTime::Date(date) => return date.and_hms_opt(0, 0, 0).unwrap(),
+            IcsTime::Floating(wall) => return wall,
+            IcsTime::Zoned(tz, wall) if Some(tz) == zone => return wall,
+            IcsTime::Zoned(tz, wall) => time_zone::wall_to_utc(tz, wall),
+            IcsTime::Utc(utc) => utc,
+        };
+        match zone {
+            Some(tz) => time_zone::utc_to_wall(tz, utc),
+            None => time_zone::utc_to_local(utc),
+        }
+    }
+}
+
+/// Reads the IANA zone a TZID names, skipping prefixes some tools add as in
+/// `/mozilla.org/20070129_1/Europe/Paris`.
+fn parse_tzid(tzid: &str) -> Option<Tz> {
+    let mut name = tzid.trim().trim_start_matches('/');
+    loop {
+        if let Ok(tz) = Tz::from_str(name) {
+            return Some(tz);
+        }
+        name = name.split_once('/')?.1;
+    }
+}
+
+fn parse_time_value(value: &str, tzid: Option<&str>, date_only: bool) -> Result<IcsTime, String> {
+    let value = value.trim();
+    if date_only || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, DATE_FORMAT)
+            .map(IcsTime::Date)
+            .map_err(|_| format!("invalid date '{}'", value));
+    }
+    let (wall, utc) = match value.strip_suffix('Z').or_else(|| value.strip_suffix('z')) {
+        Some(wall) => (wall, true),
+        None => (value, false),
+    };
+    let wall = NaiveDateTime::parse_from_str(wall, DATE_TIME_FORMAT)
+        .map_err(|_| format!("invalid date-time '{}'", value))?;
+    if utc {
+        return Ok(IcsTime::Utc(wall));
+    }
+    match tzid {
+        Some(tzid) => match parse_tzid(tzid) {
+            Some(tz) => Ok(IcsTime::Zoned(tz, wall)),
+            None => Err(format!("unknown time zone '{}'", tzid)),
+        },
+        None => Ok(IcsTime::Floating(wall)),
+    }
+}
+
+/// Reads a time property. Unknown zones fall back to floating times, noted
+/// in `problems`.
+fn parse_time(property: &Property, context: &str, problems: &mut Vec<String>) -> Result<IcsTime, String> {
+    let date_only = property.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE"));
+    match parse_time_value(&property.value, property.param("TZID"), date_only) {
+        Err(e) if e.starts_with("unknown time zone") => {
+            problems.push(format!("{}: {}, reading its times as floating", context, e));
+            parse_time_value(&property.value, None, date_only)
+        }
+        result => result,
+    }
+}
+
+/// An RFC 5545 duration such as `PT1H30M`, `P1D` or `P2W`.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}'", value);
+    let out_of_range = || format!("duration '{}' is out of range", value);
+    let text = value.trim();
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.trim_start_matches('+')),
+    };
+    let text = text.strip_prefix('P').ok_or_else(invalid)?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in text.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let n: i64 = number.parse().map_err(|_| invalid())?;
+                number.clear();
+                let step = match (unit, in_time) {
+                    ('W', false) => Duration::try_weeks(n),
+                    ('D', false) => Duration::try_days(n),
+                    ('H', true) => Duration::try_hours(n),
+                    ('M', true) => Duration::try_minutes(n),
+                    ('S', true) => Duration::try_seconds(n),
+                    _ => return Err(invalid()),
+                };
+                total = step.and_then(|step| total.checked_add(&step)).ok_or_else(out_of_range)?;
+            }
+        }
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(if negative { -total } else { total })
+}
+
+fn parse_priority(component: &Component) -> Priority {
+    match component.get("PRIORITY").and_then(|p| p.value.trim().parse::<u8>().ok()) {
+        Some(1..=4) => Priority::High,
+        Some(6..=9) => Priority::Low,
+        _ => Priority::Medium,
+    }
+}
+
+/// Reads an RRULE, converting UNTIL into the event's wall-clock time and
+/// using the named reoccurances where the rule is one of them.
+fn parse_rrule(value: &str, zone: Option<Tz>) -> Result<Reoccurance, String> {
+    let mut until = None;
+    let mut parts = Vec::new();
+    for part in value.trim().split(';').filter(|p| !p.trim().is_empty()) {
+        match part.split_once('=') {
+            Some((key, value)) if key.trim().eq_ignore_ascii_case("UNTIL") => {
+                until = Some(match parse_time_value(value, None, false)? {
+                    // A date UNTIL includes that whole day
+                    IcsTime::Date(date) => date.and_hms_opt(23, 59, 59).unwrap(),
+                    time => time.wall_in(zone),
+                });
+            }
+            // Weeks start on Monday here, which is also the default
+            Some((key, _)) if key.trim().eq_ignore_ascii_case("WKST") => {}
+            _ => parts.push(part),
+        }
+    }
+    let mut rule: RecurrenceRule = parts.join(";").parse()?;
+    if until.is_some() && rule.count.is_some() {
+        return Err("COUNT and UNTIL can't both be set".to_string());
+    }
+    rule.until = until;
+    Ok(Reoccurance::from_rule(rule))
+}
+
+/// Start and end of a component as written.
+fn component_times(
+    component: &Component,
+    context: &str,
+    problems: &mut Vec<String>,
+) -> Result<(IcsTime, Option<IcsTime>, Option<Duration>), String> {
+    let mut time = |name: &str| component.get(name).map(|p| parse_time(p, context, problems)).transpose();
+    let start = time("DTSTART")?;
+    let end = if component.kind == "VTODO" { time("DUE")? } else { time("DTEND")? };
+    let duration = component.get("DURATION").map(|p| parse_duration(&p.value)).transpose()?;
+    match (start, end) {
+        (Some(start), end) => Ok((start, end, duration)),
+        // A task with only a due time happens then
+        (None, Some(due)) => Ok((due, Some(due), None)),
+        (None, None) if component.kind == "VTODO" => Err("has no DUE or DTSTART".to_string()),
+        (None, None) => Err("has no DTSTART".to_string()),
+    }
+}
+
+/// `at` plus `duration`, for durations read from a file that may be corrupt.
+fn after(at: NaiveDateTime, duration: Duration) -> Result<NaiveDateTime, String> {
+    at.checked_add_signed(duration).ok_or_else(|| "ends past the last supported date".to_string())
+}
+
+fn build_event(component: &Component, problems: &mut Vec<String>) -> Result<Event, String> {
+    let context = component.describe();
+    let (start, end, duration) = component_times(component, &context, problems)?;
+    let (zone, all_day) = match start {
+        IcsTime::Date(_) => (None, true),
+        IcsTime::Floating(_) => (None, false),
+        IcsTime::Utc(_) => (Some(Tz::UTC), false),
+        IcsTime::Zoned(tz, _) => (Some(tz), false),
+    };
+    let start_wall = start.wall_in(zone);
+    let mut end_wall = match (end, duration) {
+        (Some(end), _) => end.wall_in(zone),
+        (None, Some(duration)) => after(start_wall, duration)?,
+        // RFC 5545: a date lasts the day, a date-time is a moment
+        (None, None) if all_day => start_wall + Days::new(1),
+        (None, None) => start_wall,
+    };
+    if all_day && component.kind == "VEVENT" {
+        // DTEND is exclusive, while all-day events here hold their last date
+        end_wall = (end_wall - Days::new(1)).max(start_wall);
+    }
+    if end_wall < start_wall {
+        return Err("ends before it starts".to_string());
+    }
+
+    let reoccurance = match component.get("RRULE") {
+        Some(rule) => parse_rrule(&rule.value, zone)?,
+        None => Reoccurance::None,
+    };
+    if component.get("RDATE").is_some() {
+        problems.push(format!("{}: RDATE isn't supported, those extra dates were left out", context));
+    }
+
+    let mut event = Event::new(
+        0,
+        component.text("SUMMARY").unwrap_or_else(|| "(no title)".to_string()),
+        start_wall,
+        end_wall,
+        parse_priority(component),
+        reoccurance,
+        component.text("DESCRIPTION").unwrap_or_default(),
+        component.completed(),
+    );
+    if all_day {
+        event.set_all_day(true);
+    } else {
+        event.set_time_zone(zone);
+    }
+    if !component.uid().is_empty() {
+        event.set_uid(exchange::uid_from(&component.uid()));
+    }
+
+    let mut state = event.get_occurrences().clone();
+    for exdate in component.properties.iter().filter(|p| p.name == "EXDATE") {
+        let date_only = exdate.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE"));
+        for value in exdate.value.split(',') {
+            let time = parse_time_value(value, exdate.param("TZID"), date_only)?;
+            state.exceptions.insert(time.wall_in(zone));
+        }
+    }
+    event.set_occurrences(state);
+    Ok(event)
+}
+
+/// Applies a component with a RECURRENCE-ID to the occurrence it changes.
+fn apply_instance(
+    event: &mut Event,
+    component: &Component,
+    problems: &mut Vec<String>,
+) -> Result<(), String> {
+    let context = component.describe();
+    let zone = event.get_time_zone();
+    let recurrence_id = component.get("RECURRENCE-ID").ok_or("has no RECURRENCE-ID")?;
+    let original = parse_time(recurrence_id, &context, problems)?.wall_in(zone);
+    let duration = event.get_end_time() - event.get_start_time();
+
+    let mut state = event.get_occurrences().clone();
+    if component.cancelled() {
+        state.exceptions.insert(original);
+        event.set_occurrences(state);
+        return Ok(());
+    }
+    let (start, end, length) = component_times(component, &context, problems)?;
+    let start = start.wall_in(zone);
+    let mut end = match (end, length) {
+        (Some(end), _) => end.wall_in(zone),
+        (None, Some(length)) => after(start, length)?,
+        (None, None) => after(start, duration)?,
+    };
+    if event.is_all_day() && component.kind == "VEVENT" && end > start {
+        end = end - Days::new(1);
+    }
+    let name = component.text("SUMMARY").filter(|name| name != event.get_name());
+    if start != original || original.checked_add_signed(duration) != Some(end) || name.is_some() {
+        state.overrides.insert(original, OccurrenceOverride { start, end, name });
+    }
+    if component.completed() && !event.get_completed() {
+        state.completed.insert(original);
+    }
+    event.set_occurrences(state);
+    Ok(())
+}
+
+/// Reads the events and tasks in an iCalendar file. Entries that can't be
+/// understood are skipped and reported in `problems`.
+pub fn import(text: &str) -> Parsed {
+    let (components, mut problems) = parse_components(text);
+    let mut events: Vec<Event> = Vec::new();
+    let mut by_uid: HashMap<String, usize> = HashMap::new();
+    let (instances, masters): (Vec<&Component>, Vec<&Component>) =
+        components.iter().partition(|c| c.get("RECURRENCE-ID").is_some());
+
+    for component in masters {
+        if component.cancelled() {
+            problems.push(format!("{}: cancelled, skipped", component.describe()));
+            continue;
+        }
+        match build_event(component, &mut problems) {
+            Ok(event) => {
+                by_uid.insert(component.uid(), events.len());
+                events.push(event);
+            }
+            Err(e) => problems.push(format!("{}: {}, skipped", component.describe(), e)),
+        }
+    }
+    for component in instances {
+        let Some(&index) = by_uid.get(&component.uid()) else {
+            problems.push(format!(
+                "{}: changes an occurrence of an event that isn't in the file, skipped",
+                component.describe()
+            ));
+            continue;
+        };
+        if let Err(e) = apply_instance(&mut events[index], component, &mut problems) {
+            problems.push(format!("{}: {}, skipped", component.describe(), e));
+        }
+    }
+    // Each of an entry's times reports the same unknown zone
+    problems.dedup();
+    Parsed { events, problems }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(events: &[&str]) -> String {
+        let mut lines = vec!["BEGIN:VCALENDAR".to_string()];
+        for event in events {
+            lines.push(format!("BEGIN:VEVENT\r\n{}\r\nEND:VEVENT", event.replace('\n', "\r\n")));
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.join("\r\n")
+    }
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn event(name: &str, start: &str, end: &str, reoccurance: Reoccurance) -> Event {
+        let (start, end) = (at(start), at(end));
+        Event::new(0, name.to_string(), start, end, Priority::Medium, reoccurance, String::new(), false)
+    }
+
+    fn round_trip(events: &[Event]) -> (String, Parsed) {
+        let mut out = Vec::new();
+        export(events, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let parsed = import(&text);
+        (text, parsed)
+    }
+
+    #[test]
+    fn events_and_tasks_survive_a_round_trip() {
+        let mut standup = event("Stand-up", "2026-10-19 09:00", "2026-10-19 09:15", Reoccurance::Weekly);
+        standup.set_time_zone(Some(chrono_tz::Europe::Paris));
+        standup.set_priority(Priority::High);
+        standup.set_note("Room 4; bring notes,\nand coffee".to_string());
+        standup.skip_occurrence(at("2026-10-26 09:00"));
+        standup.override_occurrence(
+            at("2026-11-02 09:00"),
+            OccurrenceOverride {
+                start: at("2026-11-02 10:00"),
+                end: at("2026-11-02 10:30"),
+                name: Some("Late stand-up".to_string()),
+            },
+        );
+        standup.set_occurrence_completed(at("2026-11-09 09:00"), true);
+        let mut task = event("Pay rent", "2026-10-31 17:00", "2026-10-31 17:00", Reoccurance::None);
+        task.set_completed(true);
+        let mut holiday = event("Holiday", "2026-12-24 00:00", "2026-12-26 00:00", Reoccurance::None);
+        holiday.set_all_day(true);
+
+        let originals = [standup, task, holiday];
+        let (text, parsed) = round_trip(&originals);
+        for property in ["RRULE:FREQ=WEEKLY", "EXDATE;TZID=Europe/Paris", "RECURRENCE-ID;TZID=Europe/Paris"] {
+            assert!(text.contains(property), "no {} in {}", property, text);
+        }
+        for property in ["BEGIN:VTODO", "STATUS:COMPLETED"] {
+            assert!(text.contains(property), "no {} in {}", property, text);
+        }
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.events.len(), originals.len());
+        for (original, copy) in originals.iter().zip(&parsed.events) {
+            assert_eq!(copy.get_uid(), original.get_uid());
+            assert_eq!(copy.get_name(), original.get_name());
+            assert_eq!(copy.get_start_time(), original.get_start_time());
+            assert_eq!(copy.get_end_time(), original.get_end_time());
+            assert_eq!(copy.get_time_zone(), original.get_time_zone());
+            assert_eq!(copy.is_all_day(), original.is_all_day());
+            assert_eq!(copy.get_reoccurance().to_rule(), original.get_reoccurance().to_rule());
+            assert_eq!(copy.get_note(), original.get_note());
+            assert_eq!(copy.get_completed(), original.get_completed());
+            assert_eq!(copy.get_occurrences(), original.get_occurrences());
+        }
+        assert!(matches!(parsed.events[0].get_priority(), Priority::High));
+    }
+
+    #[test]
+    fn imports_skip_events_already_in_the_planner() {
+        use crate::backend::classes::planner::Planner;
+        use crate::backend::storage::sqlite::SqliteStorage;
+
+        let storage = SqliteStorage::new(":memory:").unwrap();
+        let mut planner = Planner::with_storage("Test".to_string(), Box::new(storage));
+        let call = event("Call", "2026-10-20 09:00", "2026-10-20 10:00", Reoccurance::None);
+        let (_, parsed) = round_trip(std::slice::from_ref(&call));
+        assert_eq!(planner.import_events(parsed, false).added, 1);
+
+        // Same uid
+        let (_, parsed) = round_trip(std::slice::from_ref(&call));
+        let report = planner.import_events(parsed, false);
+        assert_eq!((report.added, report.duplicates), (0, 1));
+
+        // New uid, same name and times
+        let mut copy = call.clone();
+        copy.set_uid(uuid::Uuid::new_v4());
+        let (_, parsed) = round_trip(&[copy]);
+        let report = planner.import_events(parsed, false);
+        assert_eq!((report.added, report.duplicates), (0, 1));
+
+        // Same uid with --update overwrites
+        let mut renamed = call.clone();
+        renamed.set_name("Call back".to_string());
+        let (_, parsed) = round_trip(&[renamed]);
+        let report = planner.import_events(parsed, true);
+        assert_eq!((report.added, report.updated), (0, 1));
+        let events = planner.query(None, "start".parse().unwrap(), None);
+        let names: Vec<&String> = events.iter().map(|e| e.get_name()).collect();
+        assert_eq!(names, ["Call back"]);
+    }
+
+    #[test]
+    fn unknown_zones_fall_back_to_floating() {
+        let parsed = import(&calendar(&[
+            "UID:a\nDTSTART;TZID=Mars/Olympus:20261020T090000\nDTEND;TZID=Mars/Olympus:20261020T100000",
+        ]));
+        assert_eq!(parsed.events.len(), 1);
+        let rover = &parsed.events[0];
+        assert_eq!(rover.get_time_zone(), None);
+        assert_eq!(rover.get_start_time(), at("2026-10-20 09:00"));
+        assert_eq!(rover.get_end_time(), at("2026-10-20 10:00"));
+        assert_eq!(parsed.problems.len(), 1, "{:?}", parsed.problems);
+        assert!(parsed.problems[0].contains("unknown time zone 'Mars/Olympus'"), "{:?}", parsed.problems);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("PT1H30M"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1W2D"), Ok(Duration::days(9)));
+        assert_eq!(parse_duration("-PT15M"), Ok(Duration::minutes(-15)));
+        assert!(parse_duration("P1H").is_err());
+        assert!(parse_duration("PT5").is_err());
+    }
+
+    #[test]
+    fn duration_sets_the_end() {
+        let parsed = import(&calendar(&["UID:a\nDTSTART:20261020T090000\nDURATION:PT1H30M\nSUMMARY:Call"]));
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        let end = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap().and_hms_opt(10, 30, 0).unwrap();
+        assert_eq!(parsed.events[0].get_end_time(), end);
+    }
+
+    #[test]
+    fn huge_durations_are_skipped_not_fatal() {
+        let parsed = import(&calendar(&[
+            "UID:a\nDTSTART:20261020T090000\nDURATION:P99999999999W\nSUMMARY:Overflow",
+            "UID:b\nDTSTART:99991231T090000\nDURATION:P14000000W\nSUMMARY:Past the end",
+            "UID:c\nDTSTART:20261020T090000\nSUMMARY:Fine",
+        ]));
+        let names: Vec<&String> = parsed.events.iter().map(|e| e.get_name()).collect();
+        assert_eq!(names, ["Fine"]);
+        assert_eq!(parsed.problems.len(), 2, "{:?}", parsed.problems);
+    }
+
+    #[test]
+    fn zones_export_in_the_last_representable_year() {
+        let start = NaiveDate::MAX.and_hms_opt(9, 0, 0).unwrap();
+        let mut event = Event::new(
+            0,
+            "Far off".to_string(),
+            start,
+            start,
+            Priority::Medium,
+            Reoccurance::None,
+            String::new(),
+            false,
+        );
+        event.set_time_zone(Some(chrono_tz::Europe::Paris));
+        let mut out = Vec::new();
+        export(&[event], &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("TZID:Europe/Paris"), "{}", text);
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs;
+use std::io::{self, Read, Write};
+
+use uuid::{Builder, Uuid};
+
+use crate::backend::classes::event::Event;
+use crate::backend::enums::exchange_format::ExchangeFormat;
+use crate::backend::exchange::csv::CsvOptions;
+use crate::backend::storage::atomic::atomic_write;
+
+pub mod csv;
+pub mod ics;
+pub mod todo_txt;
+
+/// Events read from another tool's file, with a message for every entry
+/// that was skipped or only partly understood.
+#[derive(Default)]
+pub struct Parsed {
+    pub events: Vec<Event>,
+    pub problems: Vec<String>,
+}
+
+/// What importing a file did to the planner.
+#[derive(Default)]
+pub struct ImportReport {
+    pub added: usize,
+    pub updated: usize,
+    pub duplicates: usize,
+    /// Events that couldn't be written to storage, also listed in `problems`
+    pub failed: usize,
+    pub problems: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn display(&self) {
+        println!(
+            "Imported {} new event(s), updated {}, skipped {} duplicate(s).",
+            self.added, self.updated, self.duplicates
+        );
+        for problem in &self.problems {
+            println!("  {}", problem);
+        }
+    }
+}
+
+/// The contents of `path`, or of standard input for `-`.
+pub fn read_text(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Reads the events in `text`. `csv` says how to read CSV files and is
+/// ignored for other formats.
+pub fn parse(text: &str, format: ExchangeFormat, csv: &CsvOptions) -> Parsed {
+    match format {
+        ExchangeFormat::ICalendar => ics::import(text),
+        ExchangeFormat::Csv => csv::import(text, csv),
+        ExchangeFormat::TodoTxt => todo_txt::import(text),
+    }
+}
+
+/// Reads the events in `path`, or standard input for `-`.
+pub fn read_file(path: &str, format: ExchangeFormat, csv: &CsvOptions) -> io::Result<Parsed> {
+    Ok(parse(&read_text(path)?, format, csv))
+}
+
+/// Writes `events` to `path`, replacing it atomically, or to standard
+/// output without one.
+pub fn write_file(path: Option<&str>, format: ExchangeFormat, events: &[Event]) -> io::Result<()> {
+    match path {
+        Some(path) => atomic_write(path, |out| write_events(format, events, out)),
+        None => write_events(format, events, &mut io::stdout().lock()),
+    }
+}
+
+fn write_events(format: ExchangeFormat, events: &[Event], out: &mut impl Write) -> io::Result<()> {
+    match format {
+        ExchangeFormat::ICalendar => ics::export(events, out),
+        ExchangeFormat::Csv => csv::export(events, out),
+        ExchangeFormat::TodoTxt => todo_txt::export(events, out),
+    }
+}
+
+/// Whether two events look like the same entry even though their uids
+/// differ, e.g. one exported twice by a tool that makes up new uids.
+pub fn same_content(a: &Event, b: &Event) -> bool {
+    a.get_name() == b.get_name()
+        && a.is_all_day() == b.is_all_day()
+        && a.local_start() == b.local_start()
+        && a.local_end() == b.local_end()
+}
+
+/// The uid for an entry another tool identified by `id`. Our own UUIDs come
+/// back unchanged; anything else is hashed into a stable custom UUID so the
+/// same entry maps to the same event on every import.
+pub fn uid_from(id: &str) -> Uuid {
+    if let Ok(uid) = Uuid::parse_str(id.trim()) {
+        return uid;
+    }
+    // 128-bit FNV-1a
+    let mut hash: u128 = 0x6c62272e07bb014262b821756295c58d;
+    for byte in id.trim().bytes() {
+        hash ^= byte as u128;
+        hash = hash.wrapping_mul(0x0000000001000000000000000000013b);
+    }
+    Builder::from_custom_bytes(hash.to_be_bytes()).into_uuid()
+}
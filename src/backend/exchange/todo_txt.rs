@@ -0,0 +1,409 @@
+use std::io::{self, Write};
+
+use chrono::{NaiveDate, NaiveTime};
+use chrono_tz::Tz;
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::OccurrenceState;
+use crate::backend::classes::recurrence_rule::RecurrenceRule;
+use crate::backend::enums::frequency::Frequency;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::exchange::{self, Parsed};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Keys this format gives a meaning to. Note lines using one of them stay
+/// in `note:` instead of becoming key-values of their own.
+const KEYS: [&str; 10] = ["t", "due", "time", "tz", "rec", "rrule", "pri", "uid", "note", "occurrences"];
+
+/// Percent-encodes the characters that would end or split a key-value.
+fn encode(text: &str) -> String {
+    let mut encoded = String::new();
+    for c in text.chars() {
+        match c {
+            '%' | ' ' | '\t' | '\n' | '\r' => encoded.push_str(&format!("%{:02X}", c as u32)),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn decode(text: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(decoded) if byte == b'%' => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// The name as task text, percent-encoding what would otherwise be read as
+/// something else: `%` before two hex digits, tabs and line breaks, spaces
+/// that aren't single separators, colons in words that look like
+/// `key:value`, and a leading date or `(A)`.
+fn encode_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut text = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let is_hex = |offset: usize| chars.get(i + offset).is_some_and(char::is_ascii_hexdigit);
+        let lone_space = i > 0 && i + 1 < chars.len() && chars[i - 1] != ' ' && chars[i + 1] != ' ';
+        match c {
+            '%' if is_hex(1) && is_hex(2) => text.push_str("%25"),
+            '\t' | '\n' | '\r' => text.push_str(&format!("%{:02X}", c as u32)),
+            ' ' if !lone_space => text.push_str("%20"),
+            c => text.push(c),
+        }
+    }
+    let mut words: Vec<String> = text.split(' ').map(str::to_string).collect();
+    for word in words.iter_mut().filter(|w| key_value(w).is_some()) {
+        *word = word.replace(':', "%3A");
+    }
+    if let Some(first) = words.first_mut() {
+        if NaiveDate::parse_from_str(first, DATE_FORMAT).is_ok() {
+            *first = first.replacen('-', "%2D", 1);
+        } else if let [b'(', b'A'..=b'Z', b')'] = first.as_bytes() {
+            *first = first.replacen('(', "%28", 1);
+        }
+    }
+    words.join(" ")
+}
+
+/// `key` and `value` if `token` is a todo.txt `key:value`. Links such as
+/// `https://...` are left in the text.
+fn key_value(token: &str) -> Option<(&str, &str)> {
+    let (key, value) = token.split_once(':')?;
+    let valid = !key.is_empty() && !value.is_empty() && !value.starts_with("//");
+    valid.then_some((key, value))
+}
+
+fn priority_letter(priority: &Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+fn priority_from_letter(letter: char) -> Priority {
+    match letter {
+        'A' => Priority::High,
+        'B' => Priority::Medium,
+        _ => Priority::Low,
+    }
+}
+
+/// `rec:` for rules that are only a frequency and interval, which
+/// todo.txt apps understand, and `rrule:` for anything else.
+fn recurrence(reoccurance: &Reoccurance) -> Option<String> {
+    let rule = reoccurance.to_rule()?;
+    let simple = rule.by_day.is_empty() && rule.by_month_day.is_empty();
+    if !simple || rule.count.is_some() || rule.until.is_some() {
+        return Some(format!("rrule:{}", rule));
+    }
+    let unit = match rule.frequency {
+        Frequency::Daily => 'd',
+        Frequency::Weekly => 'w',
+        Frequency::Monthly => 'm',
+        Frequency::Yearly => 'y',
+    };
+    Some(format!("rec:{}{}", rule.interval, unit))
+}
+
+/// Reads a `rec:` value such as `1w`, `+2m` or `d`. The `+` (repeat from
+/// the due date rather than completion) is how this planner always repeats.
+fn parse_recurrence(value: &str) -> Result<Reoccurance, String> {
+    let value = value.strip_prefix('+').unwrap_or(value);
+    let (count, unit) = value.split_at(value.len() - value.chars().last().map_or(0, char::len_utf8));
+    let frequency = match unit {
+        "d" => Frequency::Daily,
+        "w" => Frequency::Weekly,
+        "m" => Frequency::Monthly,
+        "y" => Frequency::Yearly,
+        _ => return Err(format!("rec:{} isn't supported, read as not repeating", value)),
+    };
+    let interval = if count.is_empty() {
+        1
+    } else {
+        count
+            .parse::<u32>()
+            .map_err(|_| format!("rec:{} isn't supported, read as not repeating", value))?
+    };
+    Ok(Reoccurance::from_rule(RecurrenceRule::new(frequency, interval)))
+}
+
+fn line(event: &Event) -> String {
+    let start = event.get_start_time();
+    let end = event.get_end_time();
+    let mut parts = Vec::new();
+    if event.get_completed() {
+        // When an event was completed isn't recorded, so it's dated by its end
+        parts.push(format!("x {}", end.format(DATE_FORMAT)));
+    } else {
+        parts.push(format!("({})", priority_letter(event.get_priority())));
+    }
+    parts.push(encode_name(event.get_name()));
+    parts.push(format!("t:{}", start.format(DATE_FORMAT)));
+    parts.push(format!("due:{}", end.format(DATE_FORMAT)));
+    if !event.is_all_day() {
+        if start.time() == end.time() {
+            parts.push(format!("time:{}", start.format(TIME_FORMAT)));
+        } else {
+            parts.push(format!("time:{}-{}", start.format(TIME_FORMAT), end.format(TIME_FORMAT)));
+        }
+        if let Some(tz) = event.get_time_zone() {
+            parts.push(format!("tz:{}", tz.name()));
+        }
+    }
+    parts.extend(recurrence(event.get_reoccurance()));
+    // Completed tasks lose their priority in todo.txt, so it moves to pri:
+    if event.get_completed() {
+        parts.push(format!("pri:{}", priority_letter(event.get_priority())));
+    }
+    parts.push(format!("uid:{}", event.get_uid()));
+
+    let mut text = Vec::new();
+    for note_line in event.get_note().lines() {
+        match key_value(note_line) {
+            Some((key, value)) if !KEYS.contains(&key) && !value.contains(char::is_whitespace) => {
+                parts.push(note_line.to_string())
+            }
+            _ => text.push(note_line),
+        }
+    }
+    if !text.is_empty() {
+        parts.push(format!("note:{}", encode(&text.join("\n"))));
+    }
+    if !event.get_occurrences().is_empty() {
+        parts.push(format!("occurrences:{}", encode(&event.get_occurrences().to_json())));
+    }
+    parts.join(" ")
+}
+
+/// Writes one todo.txt task per event. Dates go in `t:` (start) and `due:`
+/// (end), with `time:` and `tz:` for timed events; the note is kept in
+/// `note:` except for lines that are themselves `key:value`s.
+pub fn export(events: &[Event], out: &mut impl Write) -> io::Result<()> {
+    for event in events {
+        writeln!(out, "{}", line(event))?;
+    }
+    Ok(())
+}
+
+fn parse_date(key: &str, value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| format!("{}:{} isn't a YYYY-MM-DD date", key, value))
+}
+
+fn parse_times(value: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let time = |text: &str| {
+        NaiveTime::parse_from_str(text, TIME_FORMAT)
+            .map_err(|_| format!("time:{} isn't HH:MM or HH:MM-HH:MM", value))
+    };
+    match value.split_once('-') {
+        Some((start, end)) => Ok((time(start)?, time(end)?)),
+        None => time(value).map(|at| (at, at)),
+    }
+}
+
+/// Reads one task. `warnings` gets anything that was read only in part.
+fn build_event(text: &str, warnings: &mut Vec<String>) -> Result<Event, String> {
+    let mut tokens: Vec<&str> = text.split_whitespace().collect();
+    let date = |tokens: &[&str]| tokens.first().and_then(|t| NaiveDate::parse_from_str(t, DATE_FORMAT).ok());
+
+    // x [completion date] [(A)] [creation date]
+    let completed = tokens.first() == Some(&"x");
+    if completed {
+        tokens.remove(0);
+        if date(&tokens).is_some() {
+            tokens.remove(0);
+        }
+    }
+    let mut priority = None;
+    if let Some(token) = tokens.first()
+        && let [b'(', letter @ b'A'..=b'Z', b')'] = token.as_bytes()
+    {
+        priority = Some(priority_from_letter(*letter as char));
+        tokens.remove(0);
+    }
+    let created = date(&tokens);
+    if created.is_some() {
+        tokens.remove(0);
+    }
+
+    let mut name = Vec::new();
+    let mut extra = Vec::new();
+    let (mut start, mut due, mut times, mut zone) = (None, None, None, None);
+    let mut reoccurance = Reoccurance::None;
+    let (mut uid, mut note, mut occurrences) = (None, None, None);
+    for token in tokens {
+        let Some((key, value)) = key_value(token) else {
+            name.push(token);
+            continue;
+        };
+        match key {
+            "t" => start = Some(parse_date(key, value)?),
+            "due" => due = Some(parse_date(key, value)?),
+            "time" => times = Some(parse_times(value)?),
+            "tz" => zone = Some(value.parse::<Tz>().map_err(|_| format!("unknown time zone '{}'", value))?),
+            "rec" => match parse_recurrence(value) {
+                Ok(rec) => reoccurance = rec,
+                Err(e) => warnings.push(e),
+            },
+            "rrule" => reoccurance = Reoccurance::from_rule(value.parse::<RecurrenceRule>()?),
+            "pri" => match value.as_bytes() {
+                [letter @ b'A'..=b'Z'] => priority = Some(priority_from_letter(*letter as char)),
+                _ => return Err(format!("pri:{} isn't a letter from A to Z", value)),
+            },
+            "uid" => uid = Some(exchange::uid_from(value)),
+            "note" => note = Some(decode(value)),
+            "occurrences" => {
+                let state = OccurrenceState::from_json(&decode(value));
+                occurrences = Some(state.map_err(|e| format!("occurrences: {}", e))?);
+            }
+            _ => extra.push(token),
+        }
+    }
+
+    if name.is_empty() {
+        return Err("the task is empty".to_string());
+    }
+    let (start_date, end_date) = match (start, due, created) {
+        (Some(start), Some(due), _) => (start, due),
+        (Some(date), None, _) | (None, Some(date), _) | (None, None, Some(date)) => (date, date),
+        (None, None, None) => return Err("has no t:, due: or creation date".to_string()),
+    };
+    let (start_time, end_time) = match times {
+        Some((start_at, end_at)) => (start_date.and_time(start_at), end_date.and_time(end_at)),
+        None => (start_date.and_hms_opt(0, 0, 0).unwrap(), end_date.and_hms_opt(0, 0, 0).unwrap()),
+    };
+    if end_time < start_time {
+        return Err("the task is due before it starts".to_string());
+    }
+
+    // Key-values this planner has no field for are kept in the note
+    let mut note_lines: Vec<String> = note.into_iter().collect();
+    note_lines.extend(extra.iter().map(|token| token.to_string()));
+    let mut event = Event::new(
+        0,
+        decode(&name.join(" ")),
+        start_time,
+        end_time,
+        priority.unwrap_or(Priority::Medium),
+        reoccurance,
+        note_lines.join("\n"),
+        completed,
+    );
+    if times.is_some() {
+        event.set_time_zone(zone);
+    } else {
+        event.set_all_day(true);
+    }
+    if let Some(uid) = uid {
+        event.set_uid(uid);
+    }
+    if let Some(occurrences) = occurrences {
+        event.set_occurrences(occurrences);
+    }
+    Ok(event)
+}
+
+/// Reads one event per task line. Tasks without a time are all-day; tasks
+/// with no date at all can't be placed in the planner and are skipped.
+pub fn import(text: &str) -> Parsed {
+    let mut parsed = Parsed::default();
+    for (index, task) in text.lines().enumerate() {
+        if task.trim().is_empty() {
+            continue;
+        }
+        let mut warnings = Vec::new();
+        match build_event(task, &mut warnings) {
+            Ok(event) => parsed.events.push(event),
+            Err(e) => parsed.problems.push(format!("line {}: {}, skipped", index + 1, e)),
+        }
+        parsed
+            .problems
+            .extend(warnings.into_iter().map(|w| format!("line {}: {}", index + 1, w)));
+    }
+    parsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(event: &Event) -> Event {
+        let mut out = Vec::new();
+        export(std::slice::from_ref(event), &mut out).unwrap();
+        let parsed = import(&String::from_utf8(out).unwrap());
+        assert!(parsed.problems.is_empty(), "{:?}", parsed.problems);
+        assert_eq!(parsed.events.len(), 1);
+        parsed.events.into_iter().next().unwrap()
+    }
+
+    fn event(name: &str, note: &str, completed: bool) -> Event {
+        let start = NaiveDate::from_ymd_opt(2026, 10, 20).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let end = start + chrono::Duration::minutes(45);
+        let (name, note) = (name.to_string(), note.to_string());
+        Event::new(0, name, start, end, Priority::High, Reoccurance::Weekly, note, completed)
+    }
+
+    #[test]
+    fn names_survive_a_round_trip() {
+        let names = [
+            "Standup 9:30 sync",
+            "2026-10-18 retro",
+            "(A) team",
+            "50% off, %41 stays",
+            "tabs\tand\nbreaks",
+            " double  spaced ",
+            "read https://example.com/a:b today",
+            "+project @home due:friday",
+        ];
+        for name in names {
+            for completed in [false, true] {
+                let imported = round_trip(&event(name, "", completed));
+                assert_eq!(imported.get_name(), name);
+                assert_eq!(imported.get_note(), "");
+            }
+        }
+    }
+
+    #[test]
+    fn fields_survive_a_round_trip() {
+        let original = event("Review", "first line\nurl:https://example.com\nh:1", true);
+        let imported = round_trip(&original);
+        assert_eq!(imported.get_uid(), original.get_uid());
+        assert_eq!(imported.get_start_time(), original.get_start_time());
+        assert_eq!(imported.get_end_time(), original.get_end_time());
+        assert!(matches!(imported.get_priority(), Priority::High));
+        assert_eq!(imported.get_reoccurance().to_string(), "Weekly");
+        assert!(imported.get_completed());
+        let mut note_lines: Vec<&str> = imported.get_note().lines().collect();
+        note_lines.sort();
+        assert_eq!(note_lines, ["first line", "h:1", "url:https://example.com"]);
+    }
+
+    #[test]
+    fn foreign_tasks() {
+        let parsed = import("(A) 2026-10-01 Call mom +family @phone due:2026-10-21 rec:2w\nno date here\n");
+        assert_eq!(parsed.events.len(), 1);
+        let event = &parsed.events[0];
+        assert_eq!(event.get_name(), "Call mom +family @phone");
+        assert!(event.is_all_day());
+        assert_eq!(event.get_start_time().date(), NaiveDate::from_ymd_opt(2026, 10, 21).unwrap());
+        assert_eq!(event.get_reoccurance().to_string(), "Fornite");
+        assert_eq!(parsed.problems.len(), 1, "{:?}", parsed.problems);
+    }
+}
@@ -0,0 +1,428 @@
+use chrono::{Days, NaiveDateTime};
+
+use crate::backend::classes::event::Event;
+use crate::backend::date_parser::{self, TimeInput};
+use crate::backend::enums::priority::Priority;
+
+/// A parsed filter expression, such as
+/// `priority:high and not done and start>=2026-10-01 and name~"review"`.
+///
+/// Terms are `field op value` or a bare flag, combined with `and` (also
+/// implied between terms), `or`, `not` and parentheses. Values with spaces
+/// or parentheses go in double quotes.
+///
+/// - `name`, `note`, `tz`, `repeat`, `uid`: `:` or `=` equal, `!=` not
+///   equal, `~` contains; all ignore case
+/// - `start`, `end`: `:`/`=` same day, `!=`, `<`, `<=`, `>`, `>=` against
+///   anything the date parser accepts. A bare date covers the whole day, so
+///   `start<=2026-10-01` includes events later that day
+/// - `priority`: equal or compared, `high` being the greatest
+/// - `id`: a number, equal or compared
+/// - `done`, `recurring`, `allday`: flags, or `done:no` and so on
+///
+/// Times are compared in the viewer's zone; recurring events match on
+/// their first occurrence.
+#[derive(Debug)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Text(TextField, Op, String),
+    Time(TimeField, Op, TimeValue),
+    Priority(Op, u8),
+    Id(Op, usize),
+    Done(bool),
+    Recurring(bool),
+    AllDay(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Equal,
+    NotEqual,
+    Contains,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TextField {
+    Name,
+    Note,
+    TimeZone,
+    Repeat,
+    Uid,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TimeField {
+    Start,
+    End,
+}
+
+/// A point in time, or a whole day when only a date was given.
+#[derive(Debug, Clone, Copy)]
+pub enum TimeValue {
+    At(NaiveDateTime),
+    Day(NaiveDateTime, NaiveDateTime),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(Op),
+    Open,
+    Close,
+}
+
+impl Filter {
+    /// Parses `input`, reading dates relative to `now` in the viewer's zone.
+    pub fn parse(input: &str, now: NaiveDateTime) -> Result<Filter, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, position: 0, now };
+        let filter = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(filter),
+            Some(token) => Err(format!("Unexpected {} in filter", describe(token))),
+        }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            Filter::And(a, b) => a.matches(event) && b.matches(event),
+            Filter::Or(a, b) => a.matches(event) || b.matches(event),
+            Filter::Not(inner) => !inner.matches(event),
+            Filter::Text(field, op, value) => {
+                let text = match field {
+                    TextField::Name => event.get_name().to_lowercase(),
+                    TextField::Note => event.get_note().to_lowercase(),
+                    TextField::TimeZone => {
+                        event.get_time_zone().map(|tz| tz.name()).unwrap_or("floating").to_lowercase()
+                    }
+                    TextField::Repeat => event.get_reoccurance().to_string().to_lowercase(),
+                    TextField::Uid => event.get_uid().to_string(),
+                };
+                match op {
+                    Op::Equal => text == *value,
+                    Op::NotEqual => text != *value,
+                    _ => text.contains(value.as_str()),
+                }
+            }
+            Filter::Time(field, op, value) => {
+                let time = match field {
+                    TimeField::Start => event.local_start(),
+                    TimeField::End => event.local_end(),
+                };
+                let (from, to) = match *value {
+                    TimeValue::At(at) => (at, at),
+                    TimeValue::Day(from, to) => (from, to),
+                };
+                match (op, value) {
+                    (Op::Equal, TimeValue::At(at)) => time == *at,
+                    (Op::NotEqual, TimeValue::At(at)) => time != *at,
+                    (Op::Equal, TimeValue::Day(..)) => time >= from && time < to,
+                    (Op::NotEqual, TimeValue::Day(..)) => time < from || time >= to,
+                    (Op::Less, _) => time < from,
+                    (Op::GreaterEqual, _) => time >= from,
+                    (Op::LessEqual, TimeValue::At(at)) => time <= *at,
+                    (Op::Greater, TimeValue::At(at)) => time > *at,
+                    (Op::LessEqual, TimeValue::Day(..)) => time < to,
+                    (Op::Greater, TimeValue::Day(..)) => time >= to,
+                    (Op::Contains, _) => false,
+                }
+            }
+            Filter::Priority(op, rank) => compare(*op, event.get_priority().rank(), *rank),
+            Filter::Id(op, id) => compare(*op, *event.get_id(), *id),
+            Filter::Done(done) => event.get_completed() == *done,
+            Filter::Recurring(recurring) => event.is_recurring() == *recurring,
+            Filter::AllDay(all_day) => event.is_all_day() == *all_day,
+        }
+    }
+}
+
+fn compare<T: Ord>(op: Op, value: T, against: T) -> bool {
+    match op {
+        Op::Equal | Op::Contains => value == against,
+        Op::NotEqual => value != against,
+        Op::Less => value < against,
+        Op::LessEqual => value <= against,
+        Op::Greater => value > against,
+        Op::GreaterEqual => value >= against,
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => format!("'{}'", word),
+        Token::Quoted(text) => format!("\"{}\"", text),
+        Token::Op(op) => format!("operator {:?}", op),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    // Values after an operator may contain ':' (times) and other symbols
+    let mut value_next = false;
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.extend(chars.next()),
+                    Some(c) => text.push(c),
+                    None => return Err("Unclosed quote in filter".to_string()),
+                }
+            }
+            tokens.push(Token::Quoted(text));
+            value_next = false;
+            continue;
+        }
+        if !value_next {
+            let op = match c {
+                '(' => Some(Token::Open),
+                ')' => Some(Token::Close),
+                ':' | '=' => Some(Token::Op(Op::Equal)),
+                '~' => Some(Token::Op(Op::Contains)),
+                '!' | '<' | '>' => {
+                    chars.next();
+                    let equals = chars.next_if_eq(&'=').is_some();
+                    let op = match (c, equals) {
+                        ('!', true) => Op::NotEqual,
+                        ('!', false) => return Err("Expected '=' after '!'".to_string()),
+                        ('<', false) => Op::Less,
+                        ('<', true) => Op::LessEqual,
+                        ('>', false) => Op::Greater,
+                        _ => Op::GreaterEqual,
+                    };
+                    tokens.push(Token::Op(op));
+                    value_next = true;
+                    continue;
+                }
+                _ => None,
+            };
+            if let Some(token) = op {
+                chars.next();
+                value_next = matches!(token, Token::Op(_));
+                tokens.push(token);
+                continue;
+            }
+        }
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            let stop = c.is_whitespace() || c == '(' || c == ')' || c == '"';
+            let symbol = matches!(c, ':' | '=' | '~' | '!' | '<' | '>');
+            if stop || (symbol && !value_next) {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+        tokens.push(Token::Word(word));
+        value_next = false;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    now: NaiveDateTime,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.and()?;
+        while self.at_keyword("or") {
+            self.position += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.unary()?;
+        loop {
+            if self.at_keyword("and") {
+                self.position += 1;
+            } else if self.peek().is_none() || self.peek() == Some(&Token::Close) || self.at_keyword("or") {
+                return Ok(filter);
+            }
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, String> {
+        if self.at_keyword("not") {
+            self.position += 1;
+            return Ok(Filter::Not(Box::new(self.unary()?)));
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                let filter = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("Missing ')' in filter".to_string()),
+                }
+            }
+            Some(Token::Word(field)) => self.term(&field.to_lowercase()),
+            Some(token) => Err(format!("Unexpected {} in filter", describe(&token))),
+            None => Err("Filter ends too early".to_string()),
+        }
+    }
+
+    fn term(&mut self, field: &str) -> Result<Filter, String> {
+        let Some(Token::Op(op)) = self.peek().cloned() else {
+            return flag(field, true);
+        };
+        self.position += 1;
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value,
+            _ => return Err(format!("Missing value after '{}'", field)),
+        };
+
+        let text_field = match field {
+            "name" => Some(TextField::Name),
+            "note" => Some(TextField::Note),
+            "tz" | "zone" => Some(TextField::TimeZone),
+            "repeat" | "recurrence" | "reoccurance" => Some(TextField::Repeat),
+            "uid" => Some(TextField::Uid),
+            _ => None,
+        };
+        if let Some(text_field) = text_field {
+            if !matches!(op, Op::Equal | Op::NotEqual | Op::Contains) {
+                return Err(format!("'{}' can only be compared with :, =, != or ~", field));
+            }
+            return Ok(Filter::Text(text_field, op, value.to_lowercase()));
+        }
+        if op == Op::Contains {
+            return Err(format!("'~' only works on text fields, not '{}'", field));
+        }
+
+        match field {
+            "start" | "end" => {
+                let time_field = if field == "start" { TimeField::Start } else { TimeField::End };
+                let time = match date_parser::parse(&value, self.now, None)? {
+                    TimeInput::At(at) => TimeValue::At(at),
+                    TimeInput::Date(date) => {
+                        let from = date.and_hms_opt(0, 0, 0).unwrap();
+                        TimeValue::Day(from, from + Days::new(1))
+                    }
+                };
+                Ok(Filter::Time(time_field, op, time))
+            }
+            "priority" => Ok(Filter::Priority(op, value.parse::<Priority>()?.rank())),
+            "id" => value
+                .parse::<usize>()
+                .map(|id| Filter::Id(op, id))
+                .map_err(|_| format!("'{}' isn't an event id", value)),
+            _ if op == Op::Equal || op == Op::NotEqual => {
+                flag(field, true)?;
+                let expected = match value.to_lowercase().as_str() {
+                    "yes" | "true" => true,
+                    "no" | "false" => false,
+                    other => return Err(format!("Expected yes or no for '{}', not '{}'", field, other)),
+                };
+                flag(field, expected == (op == Op::Equal))
+            }
+            _ => Err(format!("Unknown filter field '{}'", field)),
+        }
+    }
+}
+
+fn flag(field: &str, expected: bool) -> Result<Filter, String> {
+    match field {
+        "done" | "completed" => Ok(Filter::Done(expected)),
+        "recurring" => Ok(Filter::Recurring(expected)),
+        "allday" | "all_day" => Ok(Filter::AllDay(expected)),
+        other => Err(format!("Unknown filter field '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::enums::reoccurance::Reoccurance;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn event(name: &str, start: &str, priority: Priority, completed: bool) -> Event {
+        let (start, note) = (at(start), "Bring the slides".to_string());
+        Event::new(0, name.to_string(), start, start, priority, Reoccurance::None, note, completed)
+    }
+
+    fn matches(filter: &str, event: &Event) -> bool {
+        Filter::parse(filter, at("2026-10-18 10:30")).unwrap().matches(event)
+    }
+
+    #[test]
+    fn text_terms_ignore_case() {
+        let review = event("Design Review", "2026-10-20 14:00", Priority::High, false);
+        assert!(matches("name~review", &review));
+        assert!(matches("name:\"design review\"", &review));
+        assert!(!matches("name:design", &review));
+        assert!(matches("note~SLIDES and tz:floating", &review));
+    }
+
+    #[test]
+    fn bare_dates_cover_the_whole_day() {
+        let review = event("Review", "2026-10-20 14:00", Priority::High, false);
+        assert!(matches("start:2026-10-20", &review));
+        assert!(matches("start<=2026-10-20", &review));
+        assert!(!matches("start<2026-10-20", &review));
+        assert!(matches("start>2026-10-19 and start<\"tuesday 3pm\"", &review));
+    }
+
+    #[test]
+    fn priorities_compare_high_above_low() {
+        let chore = event("Chore", "2026-10-20 14:00", Priority::Medium, false);
+        assert!(matches("priority>low", &chore));
+        assert!(matches("priority<=medium", &chore));
+        assert!(!matches("priority:high", &chore));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let done = event("Chore", "2026-10-20 14:00", Priority::Low, true);
+        assert!(matches("priority:high done or name:chore", &done));
+        assert!(!matches("priority:high (done or name:chore)", &done));
+        assert!(matches("not (not done)", &done));
+        assert!(!matches("done:no", &done));
+    }
+
+    #[test]
+    fn rejects_malformed_filters() {
+        let now = at("2026-10-18 10:30");
+        let malformed = ["name~", "(done", "done)", "colour:red", "priority:urgent", "start>", "name~\"x"];
+        for filter in malformed {
+            assert!(Filter::parse(filter, now).is_err(), "{} should be rejected", filter);
+        }
+    }
+}
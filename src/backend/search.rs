@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use crate::backend::classes::event::Event;
+
+/// How much a term scores depending on where and how it matched.
+const NAME_WORD: u32 = 40;
+const NAME_SUBSTRING: u32 = 30;
+const NAME_FUZZY: u32 = 20;
+const NOTE_WORD: u32 = 15;
+const NOTE_SUBSTRING: u32 = 10;
+const NOTE_FUZZY: u32 = 6;
+/// Extra score when the whole query is the event's name.
+const EXACT_NAME: u32 = 100;
+/// Fuzzy matches may spread over at most this many characters per query
+/// character, so short terms don't match anything containing their letters.
+const FUZZY_SPREAD: usize = 2;
+
+/// An event matching a search, with the character ranges of `name` and
+/// `note` that matched.
+pub struct SearchHit {
+    pub event: Event,
+    pub name_matches: Vec<Range<usize>>,
+    pub note_matches: Vec<Range<usize>>,
+}
+
+/// Text lowercased one character at a time, remembering which original
+/// character each lowercased one came from (some lowercase to several).
+struct Folded {
+    chars: Vec<char>,
+    origin: Vec<usize>,
+}
+
+impl Folded {
+    fn new(text: &str) -> Self {
+        let mut chars = Vec::new();
+        let mut origin = Vec::new();
+        for (index, c) in text.chars().enumerate() {
+            for lower in c.to_lowercase() {
+                chars.push(lower);
+                origin.push(index);
+            }
+        }
+        Folded { chars, origin }
+    }
+
+    fn is_word_start(&self, position: usize) -> bool {
+        position == 0 || !self.chars[position - 1].is_alphanumeric()
+    }
+
+    /// The original character range covering folded `start..end`.
+    fn original(&self, start: usize, end: usize) -> Range<usize> {
+        self.origin[start]..self.origin[end - 1] + 1
+    }
+
+    /// Every non-overlapping occurrence of `term`.
+    fn find_all(&self, term: &[char]) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut position = 0;
+        while position + term.len() <= self.chars.len() {
+            if self.chars[position..position + term.len()] == *term {
+                found.push(position);
+                position += term.len();
+            } else {
+                position += 1;
+            }
+        }
+        found
+    }
+
+    /// The tightest run containing `term`'s characters in order, as the
+    /// positions of those characters.
+    fn find_fuzzy(&self, term: &[char]) -> Option<Vec<usize>> {
+        let mut best: Option<Vec<usize>> = None;
+        for start in (0..self.chars.len()).filter(|&p| self.chars[p] == term[0]) {
+            let mut positions = vec![start];
+            let mut position = start + 1;
+            for c in &term[1..] {
+                match self.chars[position..].iter().position(|x| x == c) {
+                    Some(offset) => {
+                        positions.push(position + offset);
+                        position += offset + 1;
+                    }
+                    None => break,
+                }
+            }
+            if positions.len() < term.len() {
+                // Later starts can't find what this one didn't
+                break;
+            }
+            let span = position - start;
+            if best.as_ref().is_none_or(|b| span < b[b.len() - 1] + 1 - b[0]) {
+                best = Some(positions);
+            }
+        }
+        best.filter(|b| b[b.len() - 1] + 1 - b[0] <= term.len() * FUZZY_SPREAD)
+    }
+}
+
+/// Where `term` matched in one field and how well.
+fn match_field(
+    text: &Folded,
+    term: &[char],
+    scores: (u32, u32, u32),
+    ranges: &mut Vec<Range<usize>>,
+) -> Option<u32> {
+    let (word, substring, fuzzy) = scores;
+    let found = text.find_all(term);
+    if !found.is_empty() {
+        let at_word = found.iter().any(|&p| text.is_word_start(p));
+        ranges.extend(found.iter().map(|&p| text.original(p, p + term.len())));
+        return Some(if at_word { word } else { substring });
+    }
+    let positions = text.find_fuzzy(term)?;
+    let gaps = positions[positions.len() - 1] + 1 - positions[0] - term.len();
+    ranges.extend(positions.iter().map(|&p| text.original(p, p + 1)));
+    Some(fuzzy.saturating_sub(gaps as u32).max(1))
+}
+
+/// Sorts `ranges` and joins those that touch or overlap.
+fn merge(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Events whose name or note contain every word of `query`, ignoring case,
+/// either as is or with other characters in between ("dntst" finds
+/// "Dentist"). Best matches come first: exact names, then words found in
+/// the name, then in the note, then fuzzy matches; ties go by start time.
+pub fn search(events: &[Event], query: &str) -> Vec<SearchHit> {
+    let terms: Vec<Vec<char>> = query.split_whitespace().map(|t| Folded::new(t).chars).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let whole_query = Folded::new(&query.split_whitespace().collect::<Vec<_>>().join(" ")).chars;
+
+    let mut scored: Vec<(u32, SearchHit)> = events
+        .iter()
+        .filter_map(|event| {
+            let name = Folded::new(event.get_name());
+            let note = Folded::new(event.get_note());
+            let mut name_matches = Vec::new();
+            let mut note_matches = Vec::new();
+            let mut score = if name.chars == whole_query { EXACT_NAME } else { 0 };
+            for term in &terms {
+                let name_scores = (NAME_WORD, NAME_SUBSTRING, NAME_FUZZY);
+                let note_scores = (NOTE_WORD, NOTE_SUBSTRING, NOTE_FUZZY);
+                let in_name = match_field(&name, term, name_scores, &mut name_matches);
+                let in_note = match_field(&note, term, note_scores, &mut note_matches);
+                score += in_name.max(in_note)?;
+            }
+            let hit = SearchHit {
+                event: event.clone(),
+                name_matches: merge(name_matches),
+                note_matches: merge(note_matches),
+            };
+            Some((score, hit))
+        })
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.event.local_start().cmp(&b.event.local_start()))
+            .then_with(|| a.event.get_id().cmp(b.event.get_id()))
+    });
+    scored.into_iter().map(|(_, hit)| hit).collect()
+}
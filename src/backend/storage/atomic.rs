@@ -0,0 +1,78 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Writes a file by filling a temporary sibling, syncing it to disk and
+/// renaming it over the target, so a crash leaves either the old or the new
+/// contents but never a truncated file.
+pub fn atomic_write<F>(file_name: &str, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<&File>) -> io::Result<()>,
+{
+    let tmp_name = format!("{}.tmp", file_name);
+    let result = (|| {
+        let file = File::create(&tmp_name)?;
+        let mut writer = BufWriter::new(&file);
+        write(&mut writer)?;
+        writer.flush()?;
+        drop(writer);
+        file.sync_all()?;
+        fs::rename(&tmp_name, file_name)?;
+        sync_parent_dir(file_name)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_name);
+    }
+    result
+}
+
+/// Makes the rename itself durable. Directories can't be opened for syncing
+/// on every platform, so failures to open one are ignored.
+fn sync_parent_dir(file_name: &str) -> io::Result<()> {
+    let parent = match Path::new(file_name).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match File::open(parent) {
+        Ok(dir) => dir.sync_all().or(Ok(())),
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::scratch_file;
+
+    #[test]
+    fn a_successful_write_replaces_the_file() {
+        let file_name = scratch_file("atomic-success", "planner.txt");
+        fs::write(&file_name, "old").unwrap();
+        atomic_write(&file_name, |writer| writer.write_all(b"new")).unwrap();
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), "new");
+        assert!(!Path::new(&format!("{}.tmp", file_name)).exists());
+    }
+
+    #[test]
+    fn a_failed_write_leaves_the_original_intact() {
+        let file_name = scratch_file("atomic-failure", "planner.txt");
+        fs::write(&file_name, "old").unwrap();
+        let result = atomic_write(&file_name, |writer| {
+            writer.write_all(b"half of the new")?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), "old");
+        assert!(!Path::new(&format!("{}.tmp", file_name)).exists());
+    }
+
+    #[test]
+    fn an_unwritable_temporary_file_leaves_the_original_intact() {
+        let file_name = scratch_file("atomic-blocked", "planner.txt");
+        fs::write(&file_name, "old").unwrap();
+        // A directory where the temporary file should go can't be created
+        fs::create_dir(format!("{}.tmp", file_name)).unwrap();
+        assert!(atomic_write(&file_name, |writer| writer.write_all(b"new")).is_err());
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), "old");
+    }
+}
@@ -0,0 +1,155 @@
+use chrono::Local;
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::backend::storage::atomic::atomic_write;
+
+/// How many timestamped backups are kept next to each planner file.
+pub const MAX_BACKUPS: usize = 5;
+
+const BACKUP_SUFFIX: &str = ".bak";
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+fn backup_prefix(file_name: &str) -> String {
+    let base = Path::new(file_name)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    format!("{}.", base)
+}
+
+fn parent_dir(file_name: &str) -> PathBuf {
+    match Path::new(file_name).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// `<file>.<timestamp>.bak` for a backup taken now.
+pub fn backup_path(file_name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}.{}{}",
+        file_name,
+        Local::now().format(STAMP_FORMAT),
+        BACKUP_SUFFIX
+    ))
+}
+
+/// Copies the planner file to `<file>.<timestamp>.bak` and prunes the oldest
+/// backups beyond `MAX_BACKUPS`. Returns `None` when there is nothing to
+/// back up yet.
+pub fn create_backup(file_name: &str) -> io::Result<Option<PathBuf>> {
+    if !Path::new(file_name).exists() {
+        return Ok(None);
+    }
+    let backup = backup_path(file_name);
+    fs::copy(file_name, &backup)?;
+
+    for stale in list_backups(file_name)?.into_iter().skip(MAX_BACKUPS) {
+        fs::remove_file(stale)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Timestamped backups of the planner file, newest first.
+pub fn list_backups(file_name: &str) -> io::Result<Vec<PathBuf>> {
+    let prefix = backup_prefix(file_name);
+    let mut backups = Vec::new();
+    let entries = match fs::read_dir(parent_dir(file_name)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(backups),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(stamp) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(BACKUP_SUFFIX))
+        else {
+            continue;
+        };
+        if chrono::NaiveDateTime::parse_from_str(stamp, STAMP_FORMAT).is_ok() {
+            backups.push(entry.path());
+        }
+    }
+    backups.sort();
+    backups.reverse();
+    Ok(backups)
+}
+
+/// Replaces the planner file with a backup, backing up the current contents
+/// first so a restore can itself be undone.
+pub fn restore_backup(file_name: &str, backup: &Path) -> io::Result<()> {
+    let contents = fs::read(backup)?;
+    create_backup(file_name)?;
+    atomic_write(file_name, |writer| io::Write::write_all(writer, &contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::scratch_file;
+
+    /// A backup as if taken at `stamp`, which sorts by age.
+    fn old_backup(file_name: &str, stamp: &str) -> PathBuf {
+        let path = PathBuf::from(format!("{}.{}{}", file_name, stamp, BACKUP_SUFFIX));
+        fs::write(&path, stamp).unwrap();
+        path
+    }
+
+    #[test]
+    fn nothing_to_back_up_without_a_file() {
+        let file_name = scratch_file("backup-missing", "planner.json");
+        assert_eq!(create_backup(&file_name).unwrap(), None);
+        assert!(list_backups(&file_name).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rotation_keeps_the_newest_backups() {
+        let file_name = scratch_file("backup-rotation", "planner.json");
+        fs::write(&file_name, "current").unwrap();
+        let old: Vec<PathBuf> = (1..=4)
+            .map(|day| old_backup(&file_name, &format!("2024010{}-000000-000", day)))
+            .collect();
+
+        // Four old ones and the new one fit
+        let first = create_backup(&file_name).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&first).unwrap(), "current");
+        assert_eq!(list_backups(&file_name).unwrap().len(), MAX_BACKUPS);
+
+        // Each further backup pushes out the oldest
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = create_backup(&file_name).unwrap().unwrap();
+        let backups = list_backups(&file_name).unwrap();
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert!(!old[0].exists() && old[1].exists());
+        assert_eq!(backups[..2], [second, first]);
+        assert_eq!(backups[MAX_BACKUPS - 1], old[1]);
+    }
+
+    #[test]
+    fn only_timestamped_backups_of_the_file_are_listed() {
+        let file_name = scratch_file("backup-listing", "planner.json");
+        let dir = Path::new(&file_name).parent().unwrap();
+        let ours = old_backup(&file_name, "20240101-000000-000");
+        fs::write(dir.join("planner.json.format2.bak"), "").unwrap();
+        fs::write(dir.join("other.json.20240101-000000-000.bak"), "").unwrap();
+        fs::write(dir.join("planner.json.tmp"), "").unwrap();
+        assert_eq!(list_backups(&file_name).unwrap(), [ours]);
+    }
+
+    #[test]
+    fn restoring_backs_up_the_current_contents_first() {
+        let file_name = scratch_file("backup-restore", "planner.json");
+        fs::write(&file_name, "current").unwrap();
+        let backup = old_backup(&file_name, "20240101-000000-000");
+
+        restore_backup(&file_name, &backup).unwrap();
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), "20240101-000000-000");
+        let newest = &list_backups(&file_name).unwrap()[0];
+        assert_eq!(fs::read_to_string(newest).unwrap(), "current");
+    }
+}
@@ -0,0 +1,274 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use std::fs::File;
+use std::io::{self, BufReader, Write};
+
+use crate::backend::classes::event::Event;
+use crate::backend::storage::Storage;
+use crate::backend::storage::atomic::atomic_write;
+use crate::backend::storage::migration::{self, CURRENT_VERSION, Step};
+
+/// Version of JSON planners written before the version was recorded.
+const UNVERSIONED: u32 = 2;
+
+const STEPS: &[Step<Value>] = &[Step {
+    to: 3,
+    description: "record the format version and give each event a uid",
+    apply: assign_uids,
+}];
+
+fn assign_uids(contents: &mut Value) -> io::Result<String> {
+    let mut uids = 0;
+    if let Some(events) = contents.get_mut("events").and_then(Value::as_array_mut) {
+        for event in events.iter_mut().filter_map(Value::as_object_mut) {
+            let uid = event.get("uid").and_then(Value::as_str).and_then(|uid| Uuid::parse_str(uid).ok());
+            if uid.is_none_or(|uid| uid.is_nil()) {
+                event.insert("uid".to_string(), Value::String(Uuid::new_v4().to_string()));
+                uids += 1;
+            }
+        }
+    }
+    if let Some(contents) = contents.as_object_mut() {
+        contents.insert("version".to_string(), Value::from(3));
+    }
+    Ok(format!("{} uid(s) assigned", uids))
+}
+
+/// On-disk layout of a JSON planner file.
+#[derive(Debug, Serialize, Deserialize)]
+struct PlannerFile {
+    #[serde(default = "unversioned")]
+    version: u32,
+    name: String,
+    next_event_id: usize,
+    events: Vec<Event>,
+}
+
+fn unversioned() -> u32 {
+    UNVERSIONED
+}
+
+/// Whole planner serialized as a single JSON document.
+pub struct JsonStorage {
+    name: String,
+    file_name: String,
+    pretty: bool,
+    next_event_id: usize,
+}
+
+impl JsonStorage {
+    pub fn new(name: String, file_name: String, pretty: bool) -> Self {
+        Self {
+            name,
+            file_name,
+            pretty,
+            next_event_id: 0,
+        }
+    }
+
+    fn read_file(&self) -> io::Result<PlannerFile> {
+        let file = File::open(&self.file_name)?;
+        serde_json::from_reader(BufReader::new(file)).map_err(io::Error::from)
+    }
+
+    fn write_file(&mut self, events: &[Event]) -> io::Result<()> {
+        let max_next = events.iter().map(|e| e.get_id() + 1).max().unwrap_or(0);
+        self.next_event_id = self.next_event_id.max(max_next);
+        let contents = PlannerFile {
+            version: CURRENT_VERSION,
+            name: self.name.clone(),
+            next_event_id: self.next_event_id,
+            events: events.to_vec(),
+        };
+
+        let pretty = self.pretty;
+        atomic_write(&self.file_name, |writer| {
+            if pretty {
+                serde_json::to_writer_pretty(&mut *writer, &contents)?;
+            } else {
+                serde_json::to_writer(&mut *writer, &contents)?;
+            }
+            writeln!(writer)
+        })
+    }
+}
+
+impl Storage for JsonStorage {
+    fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn load(&mut self) -> io::Result<Vec<Event>> {
+        let contents = match self.read_file() {
+            Ok(contents) => contents,
+            // Nothing has been saved yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        migration::check_version(&self.file_name, contents.version)?;
+        self.next_event_id = self.next_event_id.max(contents.next_event_id);
+        Ok(contents.events)
+    }
+
+    fn insert(&mut self, event: &Event) -> io::Result<()> {
+        let mut events = self.load()?;
+        events.push(event.clone());
+        self.write_file(&events)
+    }
+
+    fn update(&mut self, event: &Event) -> io::Result<()> {
+        let events: Vec<Event> = self
+            .load()?
+            .into_iter()
+            .map(|e| if e.get_id() == event.get_id() { event.clone() } else { e })
+            .collect();
+        self.write_file(&events)
+    }
+
+    fn delete(&mut self, event_id: usize) -> io::Result<()> {
+        let events: Vec<Event> = self
+            .load()?
+            .into_iter()
+            .filter(|e| *e.get_id() != event_id)
+            .collect();
+        self.write_file(&events)
+    }
+
+    fn flush(&mut self, events: &[Event]) -> io::Result<()> {
+        self.write_file(events)
+    }
+
+    fn version(&self) -> io::Result<u32> {
+        let file = match File::open(&self.file_name) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CURRENT_VERSION),
+            Err(e) => return Err(e),
+        };
+        let contents: Value = serde_json::from_reader(BufReader::new(file)).map_err(io::Error::from)?;
+        Ok(match contents.get("version").and_then(Value::as_u64) {
+            Some(version) => version as u32,
+            None => UNVERSIONED,
+        })
+    }
+
+    fn migrate(&mut self, from: u32, dry_run: bool) -> io::Result<Vec<String>> {
+        let file = File::open(&self.file_name)?;
+        let mut contents: Value = serde_json::from_reader(BufReader::new(file)).map_err(io::Error::from)?;
+        let changes = migration::apply_steps(&mut contents, from, STEPS)?;
+        if !dry_run {
+            // Written back through the usual layout so fields keep their order
+            let planner: PlannerFile = serde_json::from_value(contents).map_err(io::Error::from)?;
+            self.next_event_id = self.next_event_id.max(planner.next_event_id);
+            self.write_file(&planner.events)?;
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::{assert_same_events, sample_events, scratch_file};
+
+    #[test]
+    fn events_survive_a_save_and_load() {
+        for pretty in [false, true] {
+            let file_name = scratch_file(&format!("json-round-trip-{}", pretty), "Test.json");
+            let events = sample_events();
+            JsonStorage::new("Test".to_string(), file_name.clone(), pretty).flush(&events).unwrap();
+
+            let mut reopened = JsonStorage::new("Test".to_string(), file_name.clone(), pretty);
+            assert_same_events(&reopened.load().unwrap(), &events);
+            assert_eq!(reopened.version().unwrap(), CURRENT_VERSION);
+            let lines = std::fs::read_to_string(&file_name).unwrap().lines().count();
+            assert_eq!(lines > 1, pretty);
+        }
+    }
+
+    #[test]
+    fn single_event_changes_are_persisted() {
+        let file_name = scratch_file("json-changes", "Test.json");
+        let mut events = sample_events();
+        let mut storage = JsonStorage::new("Test".to_string(), file_name.clone(), true);
+        assert!(storage.load().unwrap().is_empty());
+        for event in &events {
+            storage.insert(event).unwrap();
+        }
+        events[1].set_name("Swim".to_string());
+        storage.update(&events[1]).unwrap();
+        storage.delete(0).unwrap();
+        events.remove(0);
+
+        let mut reopened = JsonStorage::new("Test".to_string(), file_name, true);
+        assert_same_events(&reopened.load().unwrap(), &events);
+    }
+
+    /// A planner saved before versions and uids were recorded.
+    fn unversioned_file(test: &str, uid: Uuid) -> String {
+        let file_name = scratch_file(test, "Test.json");
+        let event = |id: usize, uid: Option<Uuid>| {
+            let mut event = serde_json::json!({
+                "id": id,
+                "name": "Gym",
+                "start_time": "2026-10-21T18:00:00",
+                "end_time": "2026-10-21T19:00:00",
+                "priority": "Medium",
+                "reoccurance": "None",
+                "note": "",
+                "completed": false,
+            });
+            if let Some(uid) = uid {
+                event["uid"] = Value::String(uid.to_string());
+            }
+            event
+        };
+        let contents = serde_json::json!({
+            "name": "Test",
+            "next_event_id": 2,
+            "events": [event(0, None), event(1, Some(uid))],
+        });
+        std::fs::write(&file_name, contents.to_string()).unwrap();
+        file_name
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        let uid = Uuid::new_v4();
+        let file_name = unversioned_file("json-migration", uid);
+        let mut storage = JsonStorage::new("Test".to_string(), file_name.clone(), false);
+        assert_eq!(storage.version().unwrap(), UNVERSIONED);
+
+        let report = migration::migrate(&mut storage, false).unwrap().unwrap();
+        let change = "2 -> 3: record the format version and give each event a uid (1 uid(s) assigned)";
+        assert_eq!(report.changes, [change]);
+        assert!(std::path::Path::new(&format!("{}.format2.bak", file_name)).exists());
+        assert_eq!(storage.version().unwrap(), CURRENT_VERSION);
+        let events = storage.load().unwrap();
+        assert!(!events[0].get_uid().is_nil());
+        assert_eq!(*events[1].get_uid(), uid);
+    }
+
+    #[test]
+    fn dry_runs_and_newer_files_change_nothing() {
+        let file_name = unversioned_file("json-untouched", Uuid::new_v4());
+        let old = std::fs::read_to_string(&file_name).unwrap();
+        let mut storage = JsonStorage::new("Test".to_string(), file_name.clone(), false);
+        assert!(migration::migrate(&mut storage, true).unwrap().is_some());
+        assert_eq!(std::fs::read_to_string(&file_name).unwrap(), old);
+        assert_eq!(storage.version().unwrap(), UNVERSIONED);
+
+        let newer = serde_json::json!({
+            "version": CURRENT_VERSION + 1,
+            "name": "Test",
+            "next_event_id": 0,
+            "events": [],
+        })
+        .to_string();
+        std::fs::write(&file_name, &newer).unwrap();
+        assert!(migration::migrate(&mut storage, false).is_err());
+        assert!(storage.load().is_err());
+        assert_eq!(std::fs::read_to_string(&file_name).unwrap(), newer);
+    }
+}
@@ -0,0 +1,194 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::backend::storage::Storage;
+
+/// Version of the planner file layout this build writes.
+///
+/// 1. Text planners with unescaped pipe-separated fields.
+/// 2. Escaped text under a `#format=2` header, with the uid, occurrences,
+///    time zone and all-day fields appended as they were added; JSON and
+///    SQLite planners from before versions were recorded.
+/// 3. Every backend records its version and every event has all fields,
+///    including a uid.
+///
+/// Adding a field means bumping this and giving each backend a step that
+/// brings version `CURRENT_VERSION - 1` files up to date.
+pub const CURRENT_VERSION: u32 = 3;
+
+/// One upgrade of a planner file's contents, from the version below `to`.
+pub struct Step<T: ?Sized> {
+    pub to: u32,
+    pub description: &'static str,
+    /// Changes the contents and says what it changed
+    pub apply: fn(&mut T) -> io::Result<String>,
+}
+
+/// What migrating a planner file did, or would do on a dry run.
+#[derive(Debug)]
+pub struct MigrationReport {
+    pub file_name: String,
+    pub from: u32,
+    pub changes: Vec<String>,
+    pub dry_run: bool,
+    /// Copy of the file as it was before the migration
+    pub backup: Option<PathBuf>,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = if self.dry_run { "would be upgraded" } else { "upgraded" };
+        write!(f, "Planner {} {} from format {} to {}:", self.file_name, verb, self.from, CURRENT_VERSION)?;
+        for change in &self.changes {
+            write!(f, "\n  {}", change)?;
+        }
+        match (&self.backup, self.dry_run) {
+            (_, true) => write!(f, "\nDry run, nothing was changed."),
+            (Some(backup), false) => write!(f, "\nThe old file was saved as {}.", backup.display()),
+            (None, false) => Ok(()),
+        }
+    }
+}
+
+/// Fails for files written by a newer version, which this one would misread.
+pub fn check_version(file_name: &str, version: u32) -> io::Result<()> {
+    if version > CURRENT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "{} is format {}, but this version of the planner only reads up to format {}",
+                file_name, version, CURRENT_VERSION
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Runs every step above `from` on `contents`, in order, and lists what
+/// each one changed.
+pub fn apply_steps<T: ?Sized>(contents: &mut T, from: u32, steps: &[Step<T>]) -> io::Result<Vec<String>> {
+    let mut changes = Vec::new();
+    for step in steps.iter().filter(|step| step.to > from) {
+        let detail = (step.apply)(contents)?;
+        changes.push(format!("{} -> {}: {} ({})", step.to - 1, step.to, step.description, detail));
+    }
+    Ok(changes)
+}
+
+/// `<file>.format<version>.bak`, kept out of the rotating backups so the
+/// last copy readable by older versions isn't pruned.
+fn backup_before(file_name: &str, version: u32) -> io::Result<PathBuf> {
+    let backup = PathBuf::from(format!("{}.format{}.bak", file_name, version));
+    fs::copy(file_name, &backup)?;
+    Ok(backup)
+}
+
+/// Upgrades the planner file behind `storage` to `CURRENT_VERSION`, backing
+/// it up first. Returns `None` when it is already current; with `dry_run`
+/// the report says what would change and nothing is written.
+pub fn migrate(storage: &mut dyn Storage, dry_run: bool) -> io::Result<Option<MigrationReport>> {
+    let file_name = storage.file_name().to_string();
+    let from = storage.version()?;
+    check_version(&file_name, from)?;
+    if from == CURRENT_VERSION {
+        return Ok(None);
+    }
+    let backup = if dry_run || !Path::new(&file_name).exists() {
+        None
+    } else {
+        Some(backup_before(&file_name, from)?)
+    };
+    let changes = storage.migrate(from, dry_run)?;
+    Ok(Some(MigrationReport { file_name, from, changes, dry_run, backup }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::scratch_file;
+    use crate::backend::storage::text::TextStorage;
+
+    const VERSION_1: &str = "\
+        0|Rock | Roll|2026-10-20 09:00:00|2026-10-20 10:00:00|Low|Daily|bring | cables|false\n\
+        1|Gym|2026-10-21 18:00:00|2026-10-21 19:00:00|Medium|None||true\n";
+
+    fn to_two(applied: &mut Vec<u32>) -> io::Result<String> {
+        applied.push(2);
+        Ok("recorded".to_string())
+    }
+
+    fn to_three(applied: &mut Vec<u32>) -> io::Result<String> {
+        applied.push(3);
+        Ok("recorded".to_string())
+    }
+
+    fn steps() -> [Step<Vec<u32>>; 2] {
+        [
+            Step { to: 2, description: "second", apply: to_two },
+            Step { to: 3, description: "third", apply: to_three },
+        ]
+    }
+
+    #[test]
+    fn only_steps_above_the_file_version_run_in_order() {
+        let mut applied = Vec::new();
+        let changes = apply_steps(&mut applied, 1, &steps()).unwrap();
+        assert_eq!(applied, [2, 3]);
+        assert_eq!(changes, ["1 -> 2: second (recorded)", "2 -> 3: third (recorded)"]);
+
+        let mut applied = Vec::new();
+        apply_steps(&mut applied, 2, &steps()).unwrap();
+        assert_eq!(applied, [3]);
+
+        let mut applied = Vec::new();
+        assert!(apply_steps(&mut applied, CURRENT_VERSION, &steps()).unwrap().is_empty());
+        assert!(applied.is_empty());
+    }
+
+    #[test]
+    fn a_dry_run_leaves_the_file_untouched() {
+        let file_name = scratch_file("migration-dry-run", "Test.txt");
+        fs::write(&file_name, VERSION_1).unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+
+        let report = migrate(&mut storage, true).unwrap().unwrap();
+        assert_eq!((report.from, report.changes.len(), report.dry_run), (1, 2, true));
+        assert!(report.backup.is_none());
+        assert!(report.to_string().ends_with("Dry run, nothing was changed."));
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), VERSION_1);
+        assert!(!Path::new(&format!("{}.format1.bak", file_name)).exists());
+        assert_eq!(storage.version().unwrap(), 1);
+    }
+
+    #[test]
+    fn the_old_file_is_backed_up_before_upgrading() {
+        let file_name = scratch_file("migration-backup", "Test.txt");
+        fs::write(&file_name, VERSION_1).unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+
+        let report = migrate(&mut storage, false).unwrap().unwrap();
+        let backup = PathBuf::from(format!("{}.format1.bak", file_name));
+        assert_eq!(report.backup.as_ref(), Some(&backup));
+        assert_eq!(fs::read_to_string(&backup).unwrap(), VERSION_1);
+        assert!(report.to_string().contains(&format!("saved as {}", backup.display())));
+        assert_eq!(storage.version().unwrap(), CURRENT_VERSION);
+        assert!(migrate(&mut storage, false).unwrap().is_none());
+    }
+
+    #[test]
+    fn files_from_a_newer_version_are_refused() {
+        assert!(check_version("Test.txt", CURRENT_VERSION).is_ok());
+        let e = check_version("Test.txt", CURRENT_VERSION + 1).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+
+        let file_name = scratch_file("migration-newer", "Test.txt");
+        let newer = format!("#format={}\nwhatever comes next\n", CURRENT_VERSION + 1);
+        fs::write(&file_name, &newer).unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+        assert_eq!(migrate(&mut storage, false).unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(storage.load().unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), newer);
+    }
+}
@@ -0,0 +1,270 @@
+use chrono::NaiveDateTime;
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::backend::classes::event::Event;
+use crate::backend::enums::storage_format::StorageFormat;
+use crate::backend::storage::json::JsonStorage;
+use crate::backend::storage::sqlite::SqliteStorage;
+use crate::backend::storage::text::TextStorage;
+
+pub mod atomic;
+pub mod backup;
+pub mod json;
+pub mod migration;
+pub mod sqlite;
+pub mod text;
+
+pub const PLANNER_DIR: &str = "src/planners";
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// Persistence backend used by `Planner`. Implementations own the on-disk
+/// format; the planner only ever talks to this trait.
+pub trait Storage {
+    fn file_name(&self) -> &str;
+    fn load(&mut self) -> io::Result<Vec<Event>>;
+    fn insert(&mut self, event: &Event) -> io::Result<()>;
+    fn update(&mut self, event: &Event) -> io::Result<()>;
+    fn delete(&mut self, event_id: usize) -> io::Result<()>;
+    fn flush(&mut self, events: &[Event]) -> io::Result<()>;
+
+    /// Format version of the file on disk; files that don't exist yet are
+    /// `migration::CURRENT_VERSION`.
+    fn version(&self) -> io::Result<u32>;
+    /// Upgrades the file from version `from` to the current one, one step at
+    /// a time, and lists what each step changed. With `dry_run` the changes
+    /// are worked out but not written.
+    fn migrate(&mut self, from: u32, dry_run: bool) -> io::Result<Vec<String>>;
+
+    /// Events overlapping `[start, end)`, ordered by start time. Backends
+    /// without an index return `None` and the planner scans memory instead.
+    fn events_between(
+        &self,
+        _start: NaiveDateTime,
+        _end: NaiveDateTime,
+    ) -> io::Result<Option<Vec<Event>>> {
+        Ok(None)
+    }
+}
+
+pub fn planner_path(name: &str, format: StorageFormat) -> String {
+    format!("{}/{}.{}", PLANNER_DIR, name, format.extension())
+}
+
+/// Sniffs a planner file's format from its contents rather than trusting
+/// the extension. Empty files are treated as text.
+pub fn detect_format(file_name: &str) -> io::Result<StorageFormat> {
+    let contents = fs::read(file_name)?;
+    if contents.starts_with(SQLITE_HEADER) {
+        return Ok(StorageFormat::Sqlite);
+    }
+    let contents = String::from_utf8_lossy(&contents);
+    let trimmed = contents.trim();
+    if !trimmed.starts_with('{') {
+        return Ok(StorageFormat::Text);
+    }
+    if trimmed.contains('\n') {
+        Ok(StorageFormat::PrettyJson)
+    } else {
+        Ok(StorageFormat::Json)
+    }
+}
+
+pub fn open_storage(
+    name: &str,
+    file_name: String,
+    format: StorageFormat,
+) -> io::Result<Box<dyn Storage>> {
+    Ok(match format {
+        StorageFormat::Text => Box::new(TextStorage::new(file_name)),
+        StorageFormat::Json => Box::new(JsonStorage::new(name.to_string(), file_name, false)),
+        StorageFormat::PrettyJson => Box::new(JsonStorage::new(name.to_string(), file_name, true)),
+        StorageFormat::Sqlite => Box::new(SqliteStorage::new(&file_name)?),
+    })
+}
+
+/// Picks the storage for a named planner, preferring SQLite, then JSON, and
+/// creates the planner directory on first use. A legacy text planner is
+/// converted to pretty JSON once and the original file is left in place
+/// untouched.
+pub fn open_planner(name: &str) -> io::Result<Box<dyn Storage>> {
+    fs::create_dir_all(PLANNER_DIR)?;
+    for format in [StorageFormat::Sqlite, StorageFormat::Json] {
+        let path = planner_path(name, format);
+        if Path::new(&path).exists() {
+            let format = detect_format(&path)?;
+            return open_storage(name, path, format);
+        }
+    }
+
+    let json_path = planner_path(name, StorageFormat::Json);
+    let text_path = planner_path(name, StorageFormat::Text);
+    if Path::new(&text_path).exists() {
+        let format = detect_format(&text_path)?;
+        if format != StorageFormat::Text {
+            return open_storage(name, text_path, format);
+        }
+        let mut from = open_storage(name, text_path.clone(), StorageFormat::Text)?;
+        let mut to = open_storage(name, json_path.clone(), StorageFormat::PrettyJson)?;
+        return match convert(from.as_mut(), to.as_mut()) {
+            Ok(count) => {
+                println!("Converted {} events from {} to {}.", count, text_path, json_path);
+                Ok(to)
+            }
+            Err(e) => {
+                eprintln!("Failed to convert {} to JSON: {}", text_path, e);
+                Ok(from)
+            }
+        };
+    }
+
+    open_storage(name, json_path, StorageFormat::PrettyJson)
+}
+
+/// Copies every event from one backend into another, replacing its contents.
+pub fn convert(from: &mut dyn Storage, to: &mut dyn Storage) -> io::Result<usize> {
+    let events = from.load()?;
+    to.flush(&events)?;
+    Ok(events.len())
+}
+
+/// Moves planner files next to `keep` that would take precedence over it in
+/// `open_planner` out of the way, so the next start picks the new backend.
+/// They keep a timestamped backup name, so switching back and forth never
+/// overwrites an earlier retired file.
+pub fn retire_other_formats(keep: &str) -> io::Result<()> {
+    for other in [StorageFormat::Sqlite, StorageFormat::Json] {
+        let path = Path::new(keep).with_extension(other.extension());
+        if path != Path::new(keep) && path.exists() {
+            fs::rename(&path, backup::backup_path(&path.to_string_lossy()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::backend::classes::occurrence::OccurrenceOverride;
+    use crate::backend::classes::planner::Planner;
+    use crate::backend::enums::priority::Priority;
+    use crate::backend::enums::reoccurance::Reoccurance;
+    use crate::backend::storage::json::JsonStorage;
+
+    use std::path::PathBuf;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    /// An empty directory for one test's files under the system temp dir,
+    /// cleared on every run.
+    pub fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("to-do-tests").join(test);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    pub fn scratch_file(test: &str, file: &str) -> String {
+        scratch_dir(test).join(file).to_string_lossy().into_owned()
+    }
+
+    /// Events using every field a backend has to store.
+    pub fn sample_events() -> Vec<Event> {
+        let mut call = Event::new(
+            0,
+            "Call | Anna".to_string(),
+            at("2026-10-20 09:00"),
+            at("2026-10-20 10:00"),
+            Priority::High,
+            Reoccurance::None,
+            "Ask about\nthe \\ backslash".to_string(),
+            true,
+        );
+        call.set_time_zone(Some(chrono_tz::Europe::Paris));
+        let mut gym = Event::new(
+            1,
+            "Gym".to_string(),
+            at("2026-10-19 18:00"),
+            at("2026-10-19 19:00"),
+            Priority::Low,
+            "FREQ=WEEKLY;BYDAY=MO,TH".parse().unwrap(),
+            String::new(),
+            false,
+        );
+        gym.skip_occurrence(at("2026-10-22 18:00"));
+        gym.set_occurrence_completed(at("2026-10-19 18:00"), true);
+        gym.override_occurrence(
+            at("2026-10-26 18:00"),
+            OccurrenceOverride { start: at("2026-10-26 19:00"), end: at("2026-10-26 20:00"), name: None },
+        );
+        let mut holiday = Event::new(
+            2,
+            "Holiday".to_string(),
+            at("2026-12-24 00:00"),
+            at("2026-12-26 00:00"),
+            Priority::Medium,
+            Reoccurance::Yearly,
+            String::new(),
+            false,
+        );
+        holiday.set_all_day(true);
+        vec![call, gym, holiday]
+    }
+
+    /// Events carry no `PartialEq`, so compare everything they serialize.
+    pub fn assert_same_events(actual: &[Event], expected: &[Event]) {
+        let json = |events: &[Event]| serde_json::to_value(events).unwrap();
+        assert_eq!(json(actual), json(expected));
+    }
+
+    #[test]
+    fn formats_are_detected_from_contents() {
+        let dir = scratch_dir("detect-format");
+        let write = |file: &str, contents: &[u8]| {
+            let path = dir.join(file).to_string_lossy().into_owned();
+            fs::write(&path, contents).unwrap();
+            path
+        };
+        // The extension doesn't matter
+        assert_eq!(detect_format(&write("a.db", b"#format=3\n")).unwrap(), StorageFormat::Text);
+        assert_eq!(detect_format(&write("b.txt", b"")).unwrap(), StorageFormat::Text);
+        assert_eq!(detect_format(&write("c.txt", br#"{"events":[]}"#)).unwrap(), StorageFormat::Json);
+        let pretty = write("d.json", b"{\n  \"events\": []\n}");
+        assert_eq!(detect_format(&pretty).unwrap(), StorageFormat::PrettyJson);
+        let sqlite = dir.join("e.json").to_string_lossy().into_owned();
+        SqliteStorage::new(&sqlite).unwrap();
+        assert_eq!(detect_format(&sqlite).unwrap(), StorageFormat::Sqlite);
+        assert!(detect_format(&dir.join("missing").to_string_lossy()).is_err());
+    }
+
+    #[test]
+    fn converting_moves_every_event_and_retires_the_old_file() {
+        let json_path = scratch_file("convert-storage", "Test.json");
+        let storage = JsonStorage::new("Test".to_string(), json_path.clone(), true);
+        let mut planner = Planner::with_storage("Test".to_string(), Box::new(storage));
+        let events = sample_events();
+        for event in events.clone() {
+            planner.create_event(event).unwrap();
+        }
+
+        planner.convert_storage(StorageFormat::Sqlite).unwrap();
+        let db_path = json_path.replace(".json", ".db");
+        assert_eq!(detect_format(&db_path).unwrap(), StorageFormat::Sqlite);
+        assert!(!Path::new(&json_path).exists());
+        assert_eq!(backup::list_backups(&json_path).unwrap().len(), 1);
+        let mut reopened = open_storage("Test", db_path.clone(), StorageFormat::Sqlite).unwrap();
+        assert_same_events(&reopened.load().unwrap(), &events);
+
+        // And back again, retiring the database
+        planner.convert_storage(StorageFormat::Json).unwrap();
+        assert_eq!(detect_format(&json_path).unwrap(), StorageFormat::Json);
+        assert!(!Path::new(&db_path).exists());
+        let mut reopened = open_storage("Test", json_path, StorageFormat::Json).unwrap();
+        assert_same_events(&reopened.load().unwrap(), &events);
+    }
+}
@@ -0,0 +1,392 @@
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use rusqlite::{Connection, Row, params};
+
+use std::io;
+use uuid::Uuid;
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::OccurrenceState;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::storage::Storage;
+use crate::backend::storage::migration::{self, CURRENT_VERSION, Step};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS events (
+        id          INTEGER PRIMARY KEY,
+        uid         TEXT NOT NULL DEFAULT '',
+        occurrences TEXT NOT NULL DEFAULT '',
+        time_zone   TEXT NOT NULL DEFAULT '',
+        all_day     INTEGER NOT NULL DEFAULT 0,
+        name        TEXT NOT NULL,
+        start_time  TEXT NOT NULL,
+        end_time    TEXT NOT NULL,
+        priority    TEXT NOT NULL,
+        reoccurance TEXT NOT NULL,
+        note        TEXT NOT NULL,
+        completed   INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_events_start_time ON events (start_time);
+    CREATE INDEX IF NOT EXISTS idx_events_priority ON events (priority);
+    CREATE INDEX IF NOT EXISTS idx_events_completed ON events (completed);
+    CREATE INDEX IF NOT EXISTS idx_events_uid ON events (uid);
+";
+
+const COLUMNS: &str = "id, name, start_time, end_time, priority, reoccurance, note, completed, uid, occurrences, time_zone, all_day";
+
+/// Embedded SQLite database. Edits touch a single row instead of rewriting
+/// the whole planner, and range queries go through the start time index.
+/// Times are stored as `Event` holds them: UTC for zoned events.
+pub struct SqliteStorage {
+    file_name: String,
+    conn: Connection,
+}
+
+fn to_io(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+fn event_from_row(row: &Row) -> rusqlite::Result<Event> {
+    let parse_time = |idx: usize| -> rusqlite::Result<NaiveDateTime> {
+        let raw: String = row.get(idx)?;
+        NaiveDateTime::parse_from_str(&raw, TIME_FORMAT).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    };
+    let id: i64 = row.get(0)?;
+    let priority: String = row.get(4)?;
+    let reoccurance: String = row.get(5)?;
+
+    let uid: String = row.get(8)?;
+
+    let mut event = Event::new(
+        id as usize,
+        row.get(1)?,
+        parse_time(2)?,
+        parse_time(3)?,
+        priority.parse().unwrap_or(Priority::Medium),
+        reoccurance.parse().unwrap_or(Reoccurance::None),
+        row.get(6)?,
+        row.get(7)?,
+    );
+    // Rows from before the uid column existed are left nil for the planner
+    event.set_uid(Uuid::parse_str(&uid).unwrap_or(Uuid::nil()));
+
+    let occurrences: String = row.get(9)?;
+    let state = OccurrenceState::from_json(&occurrences).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(9, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+    event.set_occurrences(state);
+
+    let time_zone: String = row.get(10)?;
+    if !time_zone.is_empty() {
+        let tz = time_zone.parse::<Tz>().map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(10, rusqlite::types::Type::Text, e.into())
+        })?;
+        event.restore_time_zone(Some(tz));
+    }
+    event.set_all_day(row.get(11)?);
+    Ok(event)
+}
+
+/// Columns added after the first release, with their definitions. Older
+/// databases get them through `ALTER TABLE` before the schema (and its uid
+/// index) is applied.
+const ADDED_COLUMNS: &[(&str, &str)] = &[
+    ("uid", "TEXT NOT NULL DEFAULT ''"),
+    ("occurrences", "TEXT NOT NULL DEFAULT ''"),
+    ("time_zone", "TEXT NOT NULL DEFAULT ''"),
+    ("all_day", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+/// `user_version` of databases written before the version was recorded.
+const UNVERSIONED: u32 = 2;
+
+const STEPS: &[Step<Connection>] = &[Step {
+    to: 3,
+    description: "add missing columns and give each event a uid",
+    apply: add_columns_and_uids,
+}];
+
+fn has_events_table(conn: &Connection) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'events'",
+        [],
+        |row| row.get(0),
+    )
+}
+
+fn add_columns_and_uids(conn: &mut Connection) -> io::Result<String> {
+    let columns: Vec<String> = {
+        let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('events')").map_err(to_io)?;
+        let names = stmt.query_map([], |row| row.get(0)).map_err(to_io)?;
+        names.collect::<rusqlite::Result<_>>().map_err(to_io)?
+    };
+    let mut added = Vec::new();
+    for (name, definition) in ADDED_COLUMNS {
+        if !columns.iter().any(|c| c == name) {
+            conn.execute(&format!("ALTER TABLE events ADD COLUMN {} {}", name, definition), [])
+                .map_err(to_io)?;
+            added.push(*name);
+        }
+    }
+    conn.execute_batch(SCHEMA).map_err(to_io)?;
+
+    let rows: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT id, uid FROM events").map_err(to_io)?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(to_io)?;
+        rows.collect::<rusqlite::Result<_>>().map_err(to_io)?
+    };
+    let mut uids = 0;
+    for (id, uid) in rows {
+        if Uuid::parse_str(&uid).ok().is_none_or(|uid| uid.is_nil()) {
+            conn.execute("UPDATE events SET uid = ?1 WHERE id = ?2", params![Uuid::new_v4().to_string(), id])
+                .map_err(to_io)?;
+            uids += 1;
+        }
+    }
+
+    if added.is_empty() {
+        Ok(format!("no columns added, {} uid(s) assigned", uids))
+    } else {
+        Ok(format!("added {}, {} uid(s) assigned", added.join(", "), uids))
+    }
+}
+
+fn set_version(conn: &Connection, version: u32) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!("PRAGMA user_version = {}", version))
+}
+
+impl SqliteStorage {
+    /// Opens the database, creating the schema for a new one. Existing
+    /// databases are left as they are until `migrate` brings them up to date.
+    pub fn new(file_name: &str) -> io::Result<Self> {
+        let conn = Connection::open(file_name).map_err(to_io)?;
+        if !has_events_table(&conn).map_err(to_io)? {
+            conn.execute_batch(SCHEMA).map_err(to_io)?;
+            set_version(&conn, CURRENT_VERSION).map_err(to_io)?;
+        }
+        Ok(Self {
+            file_name: file_name.to_string(),
+            conn,
+        })
+    }
+
+    fn query(&self, sql: &str, args: impl rusqlite::Params) -> io::Result<Vec<Event>> {
+        let mut stmt = self.conn.prepare(sql).map_err(to_io)?;
+        let rows = stmt.query_map(args, event_from_row).map_err(to_io)?;
+        rows.collect::<rusqlite::Result<Vec<Event>>>().map_err(to_io)
+    }
+
+    fn upsert(conn: &Connection, event: &Event) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT OR REPLACE INTO events (id, name, start_time, end_time, priority, reoccurance, note, completed, uid, occurrences, time_zone, all_day)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                *event.get_id() as i64,
+                event.get_name(),
+                event.get_start_instant().format(TIME_FORMAT).to_string(),
+                event.get_end_instant().format(TIME_FORMAT).to_string(),
+                format!("{:?}", event.get_priority()),
+                event.get_reoccurance().to_string(),
+                event.get_note(),
+                event.get_completed(),
+                event.get_uid().to_string(),
+                event.get_occurrences().to_json(),
+                event.get_time_zone().map(|tz| tz.name()).unwrap_or(""),
+                event.is_all_day(),
+            ],
+        )
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn load(&mut self) -> io::Result<Vec<Event>> {
+        self.query(&format!("SELECT {} FROM events ORDER BY id", COLUMNS), [])
+    }
+
+    fn insert(&mut self, event: &Event) -> io::Result<()> {
+        Self::upsert(&self.conn, event).map_err(to_io)?;
+        Ok(())
+    }
+
+    fn update(&mut self, event: &Event) -> io::Result<()> {
+        Self::upsert(&self.conn, event).map_err(to_io)?;
+        Ok(())
+    }
+
+    fn delete(&mut self, event_id: usize) -> io::Result<()> {
+        self.conn
+            .execute("DELETE FROM events WHERE id = ?1", params![event_id as i64])
+            .map_err(to_io)?;
+        Ok(())
+    }
+
+    fn flush(&mut self, events: &[Event]) -> io::Result<()> {
+        let tx = self.conn.transaction().map_err(to_io)?;
+        tx.execute("DELETE FROM events", []).map_err(to_io)?;
+        for event in events {
+            Self::upsert(&tx, event).map_err(to_io)?;
+        }
+        tx.commit().map_err(to_io)
+    }
+
+    fn version(&self) -> io::Result<u32> {
+        let version: u32 = self.conn.query_row("PRAGMA user_version", [], |row| row.get(0)).map_err(to_io)?;
+        Ok(if version == 0 { UNVERSIONED } else { version })
+    }
+
+    /// Runs the steps in a transaction, rolled back on a dry run.
+    fn migrate(&mut self, from: u32, dry_run: bool) -> io::Result<Vec<String>> {
+        self.conn.execute_batch("BEGIN").map_err(to_io)?;
+        let result = migration::apply_steps(&mut self.conn, from, STEPS)
+            .and_then(|changes| set_version(&self.conn, CURRENT_VERSION).map(|_| changes).map_err(to_io));
+        let end = if result.is_ok() && !dry_run { "COMMIT" } else { "ROLLBACK" };
+        self.conn.execute_batch(end).map_err(to_io)?;
+        result
+    }
+
+    fn events_between(
+        &self,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> io::Result<Option<Vec<Event>>> {
+        self.query(
+            &format!(
+                "SELECT {} FROM events WHERE start_time < ?2 AND end_time >= ?1 ORDER BY start_time",
+                COLUMNS
+            ),
+            params![
+                start.format(TIME_FORMAT).to_string(),
+                end.format(TIME_FORMAT).to_string()
+            ],
+        )
+        .map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::{assert_same_events, sample_events, scratch_file};
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn events_survive_a_save_and_load() {
+        let file_name = scratch_file("sqlite-round-trip", "Test.db");
+        let events = sample_events();
+        SqliteStorage::new(&file_name).unwrap().flush(&events).unwrap();
+
+        let mut reopened = SqliteStorage::new(&file_name).unwrap();
+        assert_same_events(&reopened.load().unwrap(), &events);
+        assert_eq!(reopened.version().unwrap(), CURRENT_VERSION);
+    }
+
+    #[test]
+    fn single_event_changes_are_persisted() {
+        let file_name = scratch_file("sqlite-changes", "Test.db");
+        let mut events = sample_events();
+        let mut storage = SqliteStorage::new(&file_name).unwrap();
+        for event in &events {
+            storage.insert(event).unwrap();
+        }
+        events[1].set_name("Swim".to_string());
+        storage.update(&events[1]).unwrap();
+        storage.delete(0).unwrap();
+        events.remove(0);
+
+        let mut reopened = SqliteStorage::new(&file_name).unwrap();
+        assert_same_events(&reopened.load().unwrap(), &events);
+    }
+
+    #[test]
+    fn range_queries_return_overlapping_events_by_start() {
+        let mut storage = SqliteStorage::new(":memory:").unwrap();
+        let events = sample_events();
+        storage.flush(&events).unwrap();
+
+        let names = |start: &str, end: &str| -> Vec<String> {
+            let found = storage.events_between(at(start), at(end)).unwrap().unwrap();
+            found.iter().map(|e| e.get_name().clone()).collect()
+        };
+        assert_eq!(names("2026-10-20 00:00", "2026-10-21 00:00"), ["Call | Anna"]);
+        assert_eq!(names("2026-10-19 18:30", "2026-10-20 07:30"), ["Gym", "Call | Anna"]);
+        // Whole days of an all-day event overlap
+        assert_eq!(names("2026-12-25 12:00", "2026-12-25 13:00"), ["Holiday"]);
+        assert!(names("2026-11-01 00:00", "2026-11-02 00:00").is_empty());
+    }
+
+    /// A database from the first release: no version, no uid, occurrence,
+    /// zone or all-day columns.
+    fn first_release_database(test: &str) -> String {
+        let file_name = scratch_file(test, "Test.db");
+        let conn = Connection::open(&file_name).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE events (
+                id          INTEGER PRIMARY KEY,
+                name        TEXT NOT NULL,
+                start_time  TEXT NOT NULL,
+                end_time    TEXT NOT NULL,
+                priority    TEXT NOT NULL,
+                reoccurance TEXT NOT NULL,
+                note        TEXT NOT NULL,
+                completed   INTEGER NOT NULL
+            );
+            INSERT INTO events VALUES
+                (0, 'Gym', '2026-10-21 18:00:00', '2026-10-21 19:00:00', 'Medium', 'None', '', 0),
+                (1, 'Call', '2026-10-22 09:00:00', '2026-10-22 10:00:00', 'High', 'Daily', '', 1);",
+        )
+        .unwrap();
+        file_name
+    }
+
+    #[test]
+    fn first_release_databases_are_upgraded() {
+        let file_name = first_release_database("sqlite-migration");
+        let mut storage = SqliteStorage::new(&file_name).unwrap();
+        assert_eq!(storage.version().unwrap(), UNVERSIONED);
+
+        let report = migration::migrate(&mut storage, false).unwrap().unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert!(
+            report.changes[0].ends_with("(added uid, occurrences, time_zone, all_day, 2 uid(s) assigned)"),
+            "{:?}",
+            report
+        );
+        assert!(std::path::Path::new(&format!("{}.format2.bak", file_name)).exists());
+        assert_eq!(storage.version().unwrap(), CURRENT_VERSION);
+        let events = storage.load().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| !e.get_uid().is_nil() && !e.is_all_day()));
+        assert!(events[1].get_completed());
+
+        // A second run has nothing left to add
+        let mut conn = Connection::open(&file_name).unwrap();
+        assert_eq!(add_columns_and_uids(&mut conn).unwrap(), "no columns added, 0 uid(s) assigned");
+    }
+
+    #[test]
+    fn dry_runs_and_newer_files_change_nothing() {
+        let file_name = first_release_database("sqlite-untouched");
+        let mut storage = SqliteStorage::new(&file_name).unwrap();
+        assert!(migration::migrate(&mut storage, true).unwrap().is_some());
+        assert_eq!(storage.version().unwrap(), UNVERSIONED);
+        // Rolled back with the columns, which loading needs
+        assert!(storage.load().is_err());
+
+        set_version(&storage.conn, CURRENT_VERSION + 1).unwrap();
+        let e = migration::migrate(&mut storage, false).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(storage.version().unwrap(), CURRENT_VERSION + 1);
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+
+use uuid::Uuid;
+
+use crate::backend::classes::event::{self, Event};
+use crate::backend::storage::Storage;
+use crate::backend::storage::atomic::atomic_write;
+use crate::backend::storage::migration::{self, CURRENT_VERSION, Step};
+
+/// First line of every text planner since fields were escaped, followed by
+/// its format version. Files without it are version 1 and read with the
+/// legacy unescaped parser.
+const HEADER_PREFIX: &str = "#format=";
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// Fields of a version 3 line: the 8 original ones, then uid, occurrences,
+/// time zone and the all-day flag
+const FIELD_COUNT: usize = 12;
+
+const STEPS: &[Step<[String]>] = &[
+    Step { to: 2, description: "escape pipes and line breaks inside fields", apply: escape_lines },
+    Step { to: 3, description: "write every field and give each event a uid", apply: complete_lines },
+];
+
+fn header() -> String {
+    format!("{}{}", HEADER_PREFIX, CURRENT_VERSION)
+}
+
+/// Rewrites unescaped lines with escaped fields. Lines the legacy parser
+/// can't read are kept as they were.
+fn escape_lines(lines: &mut [String]) -> io::Result<String> {
+    let mut rewritten = 0;
+    for line in lines.iter_mut() {
+        let Some(event) = Event::from_legacy_string(line) else {
+            continue;
+        };
+        let fields = [
+            event.get_id().to_string(),
+            event.get_name().clone(),
+            event.get_start_instant().format(TIME_FORMAT).to_string(),
+            event.get_end_instant().format(TIME_FORMAT).to_string(),
+            format!("{:?}", event.get_priority()),
+            event.get_reoccurance().to_string(),
+            event.get_note().clone(),
+            event.get_completed().to_string(),
+        ];
+        *line = fields.iter().map(|f| event::escape_field(f)).collect::<Vec<_>>().join("|");
+        rewritten += 1;
+    }
+    Ok(format!("{} of {} line(s) rewritten", rewritten, lines.len()))
+}
+
+/// Fills in the optional trailing fields, giving events without a uid a
+/// new one.
+fn complete_lines(lines: &mut [String]) -> io::Result<String> {
+    let (mut completed, mut uids) = (0, 0);
+    for line in lines.iter_mut() {
+        let mut fields = event::split_escaped(line);
+        if !(8..=FIELD_COUNT).contains(&fields.len()) {
+            continue;
+        }
+        if fields.len() < FIELD_COUNT {
+            completed += 1;
+        }
+        let defaults = ["", "", "", "false"];
+        fields.extend(defaults[fields.len() - 8..].iter().map(|d| d.to_string()));
+        if Uuid::parse_str(&fields[8]).ok().is_none_or(|uid| uid.is_nil()) {
+            fields[8] = Uuid::new_v4().to_string();
+            uids += 1;
+        }
+        *line = fields.iter().map(|f| event::escape_field(f)).collect::<Vec<_>>().join("|");
+    }
+    Ok(format!("{} line(s) completed, {} uid(s) assigned", completed, uids))
+}
+
+/// Pipe-delimited text file, one event per line.
+pub struct TextStorage {
+    file_name: String,
+}
+
+impl TextStorage {
+    pub fn new(file_name: String) -> Self {
+        Self { file_name }
+    }
+
+    fn write_all(&self, events: &[Event]) -> io::Result<()> {
+        atomic_write(&self.file_name, |file| {
+            writeln!(file, "{}", header())?;
+            for event in events {
+                writeln!(file, "{}", event)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn first_line(&self) -> io::Result<Option<String>> {
+        let file = match File::open(&self.file_name) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut first = String::new();
+        BufReader::new(file).read_line(&mut first)?;
+        Ok(Some(first.trim_end().to_string()))
+    }
+
+    fn is_current(&self) -> io::Result<bool> {
+        Ok(self.first_line()?.is_some_and(|line| line == header()))
+    }
+}
+
+impl Storage for TextStorage {
+    fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    fn load(&mut self) -> io::Result<Vec<Event>> {
+        let version = self.version()?;
+        migration::check_version(&self.file_name, version)?;
+        let file = match File::open(&self.file_name) {
+            Ok(file) => file,
+            // Nothing has been saved yet
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let reader = BufReader::new(file);
+        let mut events = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if index == 0 && line.starts_with(HEADER_PREFIX) {
+                continue;
+            }
+            let event = if version >= 2 {
+                Event::from_string(&line)
+            } else {
+                Event::from_legacy_string(&line)
+            };
+            match event {
+                Some(event) => events.push(event),
+                None if !line.trim().is_empty() => {
+                    eprintln!("Skipping unreadable line {} in {}", index + 1, self.file_name);
+                }
+                None => {}
+            }
+        }
+        Ok(events)
+    }
+
+    fn insert(&mut self, event: &Event) -> io::Result<()> {
+        // Appending a current line to an older file would mix formats, so
+        // rewrite the whole file first.
+        let exists = fs::metadata(&self.file_name).is_ok();
+        if !self.is_current()? {
+            let mut events = if exists { self.load()? } else { Vec::new() };
+            events.push(event.clone());
+            return self.write_all(&events);
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.file_name)?;
+        writeln!(file, "{}", event)?;
+        file.sync_all()
+    }
+
+    fn update(&mut self, event: &Event) -> io::Result<()> {
+        let events: Vec<Event> = self
+            .load()?
+            .into_iter()
+            .map(|e| if e.get_id() == event.get_id() { event.clone() } else { e })
+            .collect();
+        self.write_all(&events)
+    }
+
+    fn delete(&mut self, event_id: usize) -> io::Result<()> {
+        let events: Vec<Event> = self
+            .load()?
+            .into_iter()
+            .filter(|e| *e.get_id() != event_id)
+            .collect();
+        self.write_all(&events)
+    }
+
+    fn flush(&mut self, events: &[Event]) -> io::Result<()> {
+        self.write_all(events)
+    }
+
+    fn version(&self) -> io::Result<u32> {
+        let Some(first) = self.first_line()? else {
+            return Ok(CURRENT_VERSION);
+        };
+        match first.strip_prefix(HEADER_PREFIX) {
+            Some(version) => version.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} has an unreadable format header '{}'", self.file_name, first),
+                )
+            }),
+            None if fs::read_to_string(&self.file_name)?.trim().is_empty() => Ok(CURRENT_VERSION),
+            None => Ok(1),
+        }
+    }
+
+    fn migrate(&mut self, from: u32, dry_run: bool) -> io::Result<Vec<String>> {
+        let mut lines: Vec<String> = fs::read_to_string(&self.file_name)?
+            .lines()
+            .filter(|line| !line.starts_with(HEADER_PREFIX) && !line.trim().is_empty())
+            .map(str::to_string)
+            .collect();
+        let changes = migration::apply_steps(lines.as_mut_slice(), from, STEPS)?;
+        if !dry_run {
+            atomic_write(&self.file_name, |file| {
+                writeln!(file, "{}", header())?;
+                for line in &lines {
+                    writeln!(file, "{}", line)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::storage::tests::scratch_file;
+
+    fn lines(file_name: &str) -> Vec<String> {
+        fs::read_to_string(file_name).unwrap().lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn version_1_files_go_through_both_steps() {
+        let file_name = scratch_file("text-version-1", "Test.txt");
+        fs::write(
+            &file_name,
+            "0|Rock | Roll|2026-10-20 09:00:00|2026-10-20 10:00:00|Low|Daily|bring | cables|false\n\
+             1|Gym|2026-10-21 18:00:00|2026-10-21 19:00:00|Medium|None||true\n",
+        )
+        .unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+        assert_eq!(storage.version().unwrap(), 1);
+
+        let report = migration::migrate(&mut storage, false).unwrap().unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert!(report.changes[0].starts_with("1 -> 2: escape pipes"), "{:?}", report);
+        assert!(report.changes[0].ends_with("(2 of 2 line(s) rewritten)"), "{:?}", report);
+        assert!(report.changes[1].starts_with("2 -> 3: write every field"), "{:?}", report);
+        assert!(report.changes[1].ends_with("(2 line(s) completed, 2 uid(s) assigned)"), "{:?}", report);
+
+        let lines = lines(&file_name);
+        assert_eq!(lines[0], header());
+        assert!(lines[1..].iter().all(|line| event::split_escaped(line).len() == FIELD_COUNT));
+        let events = storage.load().unwrap();
+        assert_eq!(events[0].get_name(), "Rock | Roll");
+        assert_eq!(events[0].get_note(), "bring | cables");
+        assert!(events[1].get_completed());
+        assert!(events.iter().all(|e| !e.get_uid().is_nil()));
+        assert_ne!(events[0].get_uid(), events[1].get_uid());
+    }
+
+    #[test]
+    fn version_2_files_only_need_the_last_step() {
+        let file_name = scratch_file("text-version-2", "Test.txt");
+        let uid = Uuid::new_v4();
+        fs::write(
+            &file_name,
+            format!(
+                "#format=2\n\
+                 0|Rock \\| Roll|2026-10-20 09:00:00|2026-10-20 10:00:00|Low|Daily|two\\nlines|false\n\
+                 1|Gym|2026-10-21 18:00:00|2026-10-21 19:00:00|Medium|None||true|{}|||true\n",
+                uid
+            ),
+        )
+        .unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+        assert_eq!(storage.version().unwrap(), 2);
+
+        let report = migration::migrate(&mut storage, false).unwrap().unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert!(report.changes[0].ends_with("(1 line(s) completed, 1 uid(s) assigned)"), "{:?}", report);
+        assert_eq!(storage.version().unwrap(), CURRENT_VERSION);
+        let events = storage.load().unwrap();
+        assert_eq!(events[0].get_name(), "Rock | Roll");
+        assert_eq!(events[0].get_note(), "two\nlines");
+        assert!(!events[0].get_uid().is_nil());
+        assert_eq!(*events[1].get_uid(), uid);
+        assert!(events[1].is_all_day());
+    }
+
+    #[test]
+    fn dry_runs_and_newer_files_change_nothing() {
+        let file_name = scratch_file("text-untouched", "Test.txt");
+        let old = "#format=2\n1|Gym|2026-10-21 18:00:00|2026-10-21 19:00:00|Medium|None||true\n";
+        fs::write(&file_name, old).unwrap();
+        let mut storage = TextStorage::new(file_name.clone());
+        assert_eq!(migration::migrate(&mut storage, true).unwrap().unwrap().changes.len(), 1);
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), old);
+
+        let newer = format!("{}{}\n", HEADER_PREFIX, CURRENT_VERSION + 1);
+        fs::write(&file_name, &newer).unwrap();
+        assert!(migration::migrate(&mut storage, false).is_err());
+        assert!(storage.load().is_err());
+        assert_eq!(fs::read_to_string(&file_name).unwrap(), newer);
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// How a wall-clock time maps onto instants in a zone around DST changes.
+pub enum Resolution {
+    Exact(DateTime<Tz>),
+    /// The clocks went back, so the time happened twice (earlier, later).
+    Ambiguous(DateTime<Tz>, DateTime<Tz>),
+    /// The clocks went forward past this time; holds the instant the same
+    /// distance after the jump (02:30 becomes 03:30).
+    Gap(DateTime<Tz>),
+}
+
+/// The IANA zone the system is running in, if it can be determined.
+pub fn local_zone() -> Option<Tz> {
+    iana_time_zone::get_timezone().ok()?.parse().ok()
+}
+
+pub fn resolve(tz: Tz, wall: NaiveDateTime) -> Resolution {
+    match tz.from_local_datetime(&wall) {
+        LocalResult::Single(dt) => Resolution::Exact(dt),
+        LocalResult::Ambiguous(earlier, later) => Resolution::Ambiguous(earlier, later),
+        LocalResult::None => {
+            // Use the offset in force just before the gap
+            let offset = (1..=48)
+                .find_map(|quarters| {
+                    tz.offset_from_local_datetime(&(wall - Duration::minutes(15 * quarters)))
+                        .earliest()
+                })
+                .map(|o| o.fix())
+                .unwrap_or_else(|| Utc.fix());
+            let utc = wall - Duration::seconds(offset.local_minus_utc() as i64);
+            Resolution::Gap(tz.from_utc_datetime(&utc))
+        }
+    }
+}
+
+/// UTC instant of a wall-clock time, taking the earlier reading of ambiguous
+/// times and shifting times in a gap forward.
+pub fn wall_to_utc(tz: Tz, wall: NaiveDateTime) -> NaiveDateTime {
+    match resolve(tz, wall) {
+        Resolution::Exact(dt) | Resolution::Ambiguous(dt, _) | Resolution::Gap(dt) => dt.naive_utc(),
+    }
+}
+
+pub fn utc_to_wall(tz: Tz, utc: NaiveDateTime) -> NaiveDateTime {
+    tz.from_utc_datetime(&utc).naive_local()
+}
+
+/// Wall-clock time in the zone the user is currently in.
+pub fn utc_to_local(utc: NaiveDateTime) -> NaiveDateTime {
+    Local.from_utc_datetime(&utc).naive_local()
+}
+
+/// The current wall-clock time in `zone`, or in the system zone for
+/// floating times.
+pub fn now_in(zone: Option<Tz>) -> NaiveDateTime {
+    match zone {
+        Some(tz) => Utc::now().with_timezone(&tz).naive_local(),
+        None => Local::now().naive_local(),
+    }
+}
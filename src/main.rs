@@ -1,11 +1,29 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+
 use crate::ui::cli::start_ui;
+use crate::ui::commands::{self, Cli};
 
 mod backend {
     pub mod classes;
+    pub mod date_parser;
+    pub mod filter;
+    pub mod search;
     pub mod enums;
+    pub mod exchange;
+    pub mod storage;
+    pub mod time_zone;
 }
 mod ui;
 
-fn main() {
-    start_ui();
-}
\ No newline at end of file
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.command {
+        Some(command) => commands::run(command, cli.format),
+        None => {
+            start_ui();
+            ExitCode::SUCCESS
+        }
+    }
+}
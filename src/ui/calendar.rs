@@ -0,0 +1,232 @@
+use chrono::{Datelike, Days, Months, NaiveDate, NaiveDateTime, Timelike};
+use ratatui::crossterm::style::{Color, Stylize};
+use ratatui::crossterm::terminal;
+
+use crate::backend::classes::occurrence::Occurrence;
+use crate::backend::classes::planner::Planner;
+use crate::backend::enums::priority::Priority;
+use crate::ui::output;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MIN_CELL_WIDTH: usize = 4;
+const MAX_CELL_WIDTH: usize = 24;
+/// Below this a month cell only shows the day number and a busy marker
+const NAMES_CELL_WIDTH: usize = 8;
+/// Event names shown per month cell before collapsing into "+N more"
+const MONTH_CELL_LINES: usize = 3;
+/// Hours the week view always shows, widened to fit earlier or later events
+const WORK_DAY: (u32, u32) = (8, 18);
+const HOUR_GUTTER: usize = 6;
+
+/// How wide to draw and whether to use colors.
+pub struct Canvas {
+    pub width: usize,
+    pub color: bool,
+}
+
+impl Canvas {
+    /// The terminal's width (80 columns when it can't be told), with colors
+    /// only when writing to a terminal and `NO_COLOR` isn't set.
+    pub fn detect() -> Self {
+        let width = terminal::size().map(|(columns, _)| columns as usize).unwrap_or(80);
+        Self { width, color: output::color_enabled() }
+    }
+
+    fn cell_width(&self, gutter: usize) -> usize {
+        // 8 borders around 7 columns
+        (self.width.saturating_sub(gutter + 8) / 7).clamp(MIN_CELL_WIDTH, MAX_CELL_WIDTH)
+    }
+
+    fn paint(&self, text: String, color: Option<Color>, highlight: bool) -> String {
+        if !self.color {
+            return text;
+        }
+        let mut styled = text.stylize();
+        if let Some(color) = color {
+            styled = styled.with(color);
+        }
+        if highlight {
+            styled = styled.reverse();
+        }
+        styled.to_string()
+    }
+}
+
+fn priority_color(priority: &Priority) -> Color {
+    match priority {
+        Priority::High => Color::Red,
+        Priority::Medium => Color::Yellow,
+        Priority::Low => Color::Green,
+    }
+}
+
+/// Cuts `text` to `width` characters, marking the cut, and pads it out.
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = if text.chars().count() > width {
+        text.chars().take(width.saturating_sub(1)).chain(['~']).collect()
+    } else {
+        text.to_string()
+    };
+    let padding = width.saturating_sub(fitted.chars().count());
+    fitted.extend(std::iter::repeat_n(' ', padding));
+    fitted
+}
+
+fn separator(gutter: usize, cell: usize) -> String {
+    format!("{}+{}", " ".repeat(gutter), format!("{}+", "-".repeat(cell)).repeat(7))
+}
+
+fn row(gutter: String, cells: Vec<String>) -> String {
+    format!("{}|{}|", gutter, cells.join("|"))
+}
+
+fn occurrence_label(canvas: &Canvas, occurrence: &Occurrence, width: usize) -> String {
+    let mark = if occurrence.completed { "x " } else { "" };
+    let label = fit(&format!("{}{}", mark, occurrence.name), width);
+    canvas.paint(label, Some(priority_color(occurrence.event.get_priority())), false)
+}
+
+/// A month as a grid of weeks, listing each day's events colored by
+/// priority. Narrow terminals get day numbers with a `*` on busy days.
+pub fn render_month(planner: &Planner, month: NaiveDate, today: NaiveDate, canvas: &Canvas) -> String {
+    let first = month.with_day(1).unwrap();
+    let last = first + Months::new(1) - Days::new(1);
+    let grid_start = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    let grid_end = last + Days::new(6 - last.weekday().num_days_from_monday() as u64);
+    let days = planner.occurrences_by_day(grid_start, grid_end);
+    let cell = canvas.cell_width(0);
+    let show_names = cell >= NAMES_CELL_WIDTH;
+
+    let title = first.format("%B %Y").to_string();
+    let total_width = cell * 7 + 8;
+    let mut lines = vec![format!("{:^width$}", title, width = total_width)];
+    lines.push(separator(0, cell));
+    lines.push(row(String::new(), WEEKDAYS.iter().map(|d| fit(d, cell)).collect()));
+    lines.push(separator(0, cell));
+
+    for week in days.chunks(7) {
+        let numbers = week
+            .iter()
+            .map(|(date, occurrences)| {
+                let busy = if !show_names && !occurrences.is_empty() { "*" } else { "" };
+                let number = fit(&format!("{:>2}{}", date.day(), busy), cell);
+                let dim = (date.month() != first.month()).then_some(Color::DarkGrey);
+                canvas.paint(number, dim, *date == today)
+            })
+            .collect();
+        lines.push(row(String::new(), numbers));
+
+        if show_names {
+            for line in 0..MONTH_CELL_LINES {
+                let cells = week
+                    .iter()
+                    .map(|(_, occurrences)| {
+                        let overflow = occurrences.len() > MONTH_CELL_LINES;
+                        if overflow && line == MONTH_CELL_LINES - 1 {
+                            fit(&format!("+{} more", occurrences.len() - line), cell)
+                        } else {
+                            match occurrences.get(line) {
+                                Some(occurrence) => occurrence_label(canvas, occurrence, cell),
+                                None => fit("", cell),
+                            }
+                        }
+                    })
+                    .collect();
+                lines.push(row(String::new(), cells));
+            }
+        }
+        lines.push(separator(0, cell));
+    }
+    lines.join("\n")
+}
+
+/// The week containing `date` as 7 columns with hours down the side. All-day
+/// and multi-day events go in a row above the hours.
+pub fn render_week(planner: &Planner, date: NaiveDate, today: NaiveDate, canvas: &Canvas) -> String {
+    let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+    let days = planner.occurrences_by_day(monday, monday + Days::new(6));
+    let cell = canvas.cell_width(HOUR_GUTTER);
+    let gutter = |text: &str| fit(text, HOUR_GUTTER);
+
+    // Split each day into all-day and multi-day entries and timed ones
+    // clipped to the day
+    let mut all_day: Vec<Vec<&Occurrence>> = Vec::new();
+    let mut timed: Vec<Vec<(NaiveDateTime, NaiveDateTime, &Occurrence)>> = Vec::new();
+    for (day, occurrences) in &days {
+        let day_start = day.and_hms_opt(0, 0, 0).unwrap();
+        let day_end = day_start + Days::new(1);
+        let spans_days = |o: &Occurrence| o.end > o.start.date().and_hms_opt(0, 0, 0).unwrap() + Days::new(1);
+        let (whole, parts): (Vec<&Occurrence>, Vec<&Occurrence>) =
+            occurrences.iter().partition(|o| o.event.is_all_day() || spans_days(o));
+        all_day.push(whole);
+        timed.push(parts.into_iter().map(|o| (o.start.max(day_start), o.end.min(day_end), o)).collect());
+    }
+
+    let (mut first_hour, mut last_hour) = WORK_DAY;
+    for (start, end, _) in timed.iter().flatten() {
+        first_hour = first_hour.min(start.hour());
+        let end_hour = if end.time().hour() == 0 && end.date() > start.date() { 24 } else { end.hour() + 1 };
+        last_hour = last_hour.max(end_hour.min(24));
+    }
+
+    let mut lines = vec![separator(HOUR_GUTTER, cell)];
+    let headers = days
+        .iter()
+        .map(|(day, _)| {
+            let weekday = WEEKDAYS[day.weekday().num_days_from_monday() as usize];
+            canvas.paint(fit(&format!("{} {}", weekday, day.day()), cell), None, *day == today)
+        })
+        .collect();
+    lines.push(row(gutter(""), headers));
+    lines.push(separator(HOUR_GUTTER, cell));
+
+    let all_day_rows = all_day.iter().map(Vec::len).max().unwrap_or(0).min(MONTH_CELL_LINES);
+    for line in 0..all_day_rows {
+        let cells = all_day
+            .iter()
+            .map(|entries| match entries.get(line) {
+                Some(occurrence) => occurrence_label(canvas, occurrence, cell),
+                None => fit("", cell),
+            })
+            .collect();
+        lines.push(row(gutter(if line == 0 { "all" } else { "" }), cells));
+    }
+    if all_day_rows > 0 {
+        lines.push(separator(HOUR_GUTTER, cell));
+    }
+
+    for hour in first_hour..last_hour {
+        let cells = timed
+            .iter()
+            .zip(&days)
+            .map(|(entries, (day, _))| {
+                let hour_start = day.and_hms_opt(hour, 0, 0).unwrap();
+                let hour_end = hour_start + chrono::Duration::hours(1);
+                let starting: Vec<_> =
+                    entries.iter().filter(|(s, _, _)| *s >= hour_start && *s < hour_end).collect();
+                let ongoing = entries.iter().find(|(s, e, _)| *s < hour_start && *e > hour_start);
+                match (starting.first(), ongoing) {
+                    (Some((start, _, occurrence)), _) => {
+                        let more = match starting.len() {
+                            1 => String::new(),
+                            count => format!(" +{}", count - 1),
+                        };
+                        let text = format!("{} {}", start.format("%H:%M"), occurrence.name);
+                        let width = cell.saturating_sub(more.chars().count());
+                        let color = priority_color(occurrence.event.get_priority());
+                        format!("{}{}", canvas.paint(fit(&text, width), Some(color), false), more)
+                    }
+                    // Still running from an earlier hour
+                    (None, Some((_, _, occurrence))) => {
+                        let color = priority_color(occurrence.event.get_priority());
+                        canvas.paint(fit("  |", cell), Some(color), false)
+                    }
+                    (None, None) => fit("", cell),
+                }
+            })
+            .collect();
+        lines.push(row(gutter(&format!("{:02}:00", hour)), cells));
+    }
+    lines.push(separator(HOUR_GUTTER, cell));
+    lines.join("\n")
+}
@@ -1,20 +1,25 @@
-use crate::ui::ui_helpers::{display_menu, get_choice, event_creater, adjust_event};
-use crate::backend::classes::planner::{self, Planner};
+use crate::ui::ui_helpers::{display_menu, get_choice, event_creater, adjust_event, change_storage_format, restore_backup, show_agenda, show_calendar, find_events, search_events, import_export, write_report};
+use crate::backend::classes::planner::Planner;
 use chrono::Local;
 
 pub fn start_ui() {
 
-    let mut planner = Planner::new("Planner".to_string());
+    let mut planner = match Planner::new("Planner".to_string()) {
+        Ok(planner) => planner,
+        Err(e) => {
+            println!("Error opening planner: {}", e);
+            return;
+        }
+    };
 
-    match planner.load_events_file() {
-        Ok(events) => {
-            for event in events {
-                planner.add_event(event);
-            }
-            println!("Successfully loaded events from file.");
+    match planner.load_events() {
+        Ok(_) => {
+            println!("Successfully loaded events for planner '{}'.", planner.get_name());
         }
+        // Carrying on would save an empty planner over the unreadable file
         Err(e) => {
-            println!("Error loading events from file: {}", e);
+            println!("Error loading events: {}", e);
+            return;
         }
     }
 
@@ -26,7 +31,21 @@ pub fn start_ui() {
             Some(2) => event_creater(&mut planner),
             Some(3) => adjust_event(&mut planner),
             Some(4) => planner.list_events(),
-            Some(5) => break,
+            Some(5) => change_storage_format(&mut planner),
+            Some(6) => restore_backup(&mut planner),
+            Some(7) => show_agenda(&planner),
+            Some(8) => show_calendar(&planner),
+            Some(9) => find_events(&planner),
+            Some(10) => search_events(&planner),
+            Some(11) => import_export(&mut planner),
+            Some(12) => write_report(&planner),
+            Some(13) => {
+                // Edits are written as they're made; only retry ones that failed
+                if planner.has_unsaved_changes() && let Err(e) = planner.save_events() {
+                    println!("Failed to save events: {}", e);
+                }
+                break;
+            }
             _ => println!("Invalid choice, please try again."),
         }
     }
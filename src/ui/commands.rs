@@ -0,0 +1,629 @@
+use std::io::Write;
+use std::process::ExitCode;
+
+use chrono::{Days, Local, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use clap::{Args, Parser, Subcommand};
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence;
+use crate::backend::classes::planner::Planner;
+use crate::backend::date_parser::{self, TimeInput};
+use crate::backend::exchange;
+use crate::backend::exchange::csv::{ColumnMapping, CsvOptions};
+use crate::backend::enums::agenda_range::AgendaRange;
+use crate::backend::enums::exchange_format::ExchangeFormat;
+use crate::backend::enums::output_format::OutputFormat;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::enums::report_format::ReportFormat;
+use crate::backend::enums::sort_key::SortKey;
+use crate::backend::filter::Filter;
+use crate::backend::storage::atomic::atomic_write;
+use crate::backend::time_zone;
+use crate::ui::calendar::{self, Canvas};
+use crate::ui::{output, report, tui};
+
+/// Rust To-Do Planner. Without a command the interactive menu starts.
+///
+/// Exit codes: 0 on success, 1 when the command fails (unknown event id,
+/// storage errors) and 2 for invalid arguments.
+#[derive(Parser)]
+#[command(name = "to-do", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// How list, today, agenda, search, show and edit print events: text, json, jsonl, csv
+    /// or tsv. The fields are listed in the README
+    #[arg(long, global = true, default_value = "text")]
+    pub format: OutputFormat,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Create an event and print its id
+    Add(AddArgs),
+    /// Print every event, or those matching a filter
+    List(ListArgs),
+    /// Print today's events
+    Today,
+    /// Print a single event
+    Show { id: usize },
+    /// Search event names and notes, best matches first. Letters may be
+    /// skipped, so "dntst" finds "Dentist"
+    Search {
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Print at most this many events
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Print events grouped by day with overdue items first, this week by default
+    Agenda(AgendaArgs),
+    /// Draw a month grid, or a week with hours down the side
+    Calendar {
+        /// Draw the week instead of the month
+        #[arg(long)]
+        week: bool,
+        /// A day in the month or week to draw, today by default
+        #[arg(long)]
+        date: Option<String>,
+        /// Columns to fit in, the terminal's width by default
+        #[arg(long)]
+        width: Option<usize>,
+    },
+    /// Change one or more fields of an event
+    Edit(EditArgs),
+    /// Mark an event completed. For recurring events this is today's
+    /// occurrence unless --on or --series says otherwise
+    Done {
+        id: usize,
+        /// Only the occurrence on this date (e.g. today, 03-14-2026)
+        #[arg(long)]
+        on: Option<String>,
+        /// The whole series of a recurring event, every occurrence at once
+        #[arg(long, conflicts_with = "on")]
+        series: bool,
+        /// Mark it not completed instead
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Delete an event
+    Delete { id: usize },
+    /// Open the full-screen calendar and agenda
+    Tui,
+    /// Add the events in a file from another calendar tool, skipping ones
+    /// already in the planner
+    Import(ImportArgs),
+    /// Write events to a file other calendar tools can read
+    Export(ExportArgs),
+    /// Upgrade the planner file to the current format. Loading does this
+    /// too; --dry-run only shows what would change
+    Migrate {
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Write the agenda as a Markdown checklist or an HTML page with a
+    /// calendar table, this week by default
+    Report(ReportArgs),
+}
+
+/// Times accept anything the menu does: `tomorrow 3pm`, `next friday`,
+/// `in 2 hours`, `03-14-2026 15:00`, ISO 8601. A date alone is all day.
+#[derive(Args)]
+pub struct AddArgs {
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    start: String,
+    /// End time, or a duration after the start such as `+45m`. Defaults to the start
+    #[arg(long)]
+    end: Option<String>,
+    /// IANA zone such as Europe/Paris, or `floating`. Defaults to the system zone
+    #[arg(long)]
+    tz: Option<String>,
+    #[arg(long)]
+    all_day: bool,
+    #[arg(long, default_value = "medium")]
+    priority: Priority,
+    /// None, Daily, Weekly, Monthly, Yearly, Fornite or an RRULE
+    #[arg(long, default_value = "none")]
+    repeat: Reoccurance,
+    #[arg(long, default_value = "")]
+    note: String,
+    #[arg(long)]
+    completed: bool,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// Only events matching this, e.g. 'priority:high and not done and name~"review"'.
+    /// The syntax is described in the README
+    #[arg(long)]
+    filter: Option<String>,
+    /// start, end, name, priority or id, with a leading - to reverse
+    #[arg(long, default_value = "start", allow_hyphen_values = true)]
+    sort: SortKey,
+    /// Print at most this many events
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// The file to read, or - for standard input
+    file: String,
+    /// ics, csv or todo (todo.txt); taken from the file's extension by default
+    #[arg(long = "type")]
+    file_type: Option<ExchangeFormat>,
+    /// Overwrite events already imported (same uid) instead of skipping them
+    #[arg(long)]
+    update: bool,
+    /// CSV: the column holding a field, by header or number, e.g. --map "start=Due Date".
+    /// Columns named after a field are found without one
+    #[arg(long = "map", value_name = "FIELD=COLUMN")]
+    mappings: Vec<ColumnMapping>,
+    /// CSV: a chrono format for dates and times, e.g. "%d/%m/%Y %H:%M"; may be repeated
+    #[arg(long = "date-format", value_name = "FORMAT")]
+    date_formats: Vec<String>,
+    /// CSV: the character between columns
+    #[arg(long, default_value_t = ',')]
+    delimiter: char,
+    /// CSV: the first row is data, so columns must be mapped by number
+    #[arg(long)]
+    no_header: bool,
+    /// CSV: the zone of times in files without a time zone column; the system zone by default
+    #[arg(long)]
+    tz: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The file to write; standard output without one
+    file: Option<String>,
+    /// ics, csv or todo (todo.txt); taken from the file's extension by default
+    #[arg(long = "type")]
+    file_type: Option<ExchangeFormat>,
+    /// Only export events matching this filter, as for `list`
+    #[arg(long)]
+    filter: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// The file to write; Markdown on standard output without one
+    file: Option<String>,
+    /// md or html; taken from the file's extension by default
+    #[arg(long = "type")]
+    file_type: Option<ReportFormat>,
+    #[command(flatten)]
+    range: AgendaArgs,
+}
+
+#[derive(Args)]
+pub struct AgendaArgs {
+    /// Today and the following days, 1 being just today
+    #[arg(long, conflicts_with_all = ["month", "from"])]
+    days: Option<u32>,
+    /// A month such as 2026-11, 11-2026 or "next month"
+    #[arg(long, conflicts_with = "from")]
+    month: Option<String>,
+    /// First day of a range, e.g. today or 03-14-2026
+    #[arg(long, requires = "to")]
+    from: Option<String>,
+    /// Last day of the range, included
+    #[arg(long, requires = "from")]
+    to: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    id: usize,
+    #[arg(long)]
+    name: Option<String>,
+    /// New start, in the event's (possibly new) zone
+    #[arg(long)]
+    start: Option<String>,
+    /// New end, or a duration after the start such as `+45m`
+    #[arg(long)]
+    end: Option<String>,
+    /// IANA zone or `floating`; the wall-clock times are kept
+    #[arg(long)]
+    tz: Option<String>,
+    #[arg(long)]
+    all_day: Option<bool>,
+    #[arg(long)]
+    priority: Option<Priority>,
+    #[arg(long)]
+    repeat: Option<Reoccurance>,
+    #[arg(long)]
+    note: Option<String>,
+    #[arg(long)]
+    completed: Option<bool>,
+}
+
+enum Failure {
+    /// The arguments don't make sense, exit code 2
+    Invalid(String),
+    /// A valid command that couldn't be carried out, exit code 1
+    Failed(String),
+}
+
+/// Runs a single command against the planner and reports how it went.
+pub fn run(command: Command, format: OutputFormat) -> ExitCode {
+    let result = Planner::new("Planner".to_string())
+        .map_err(|e| Failure::Failed(format!("couldn't open planner: {}", e)))
+        .and_then(|mut planner| {
+            // Loading would already migrate, so this runs on the file as it is
+            if let Command::Migrate { dry_run } = command {
+                return migrate(&mut planner, dry_run);
+            }
+            planner
+                .load_events()
+                .map_err(|e| Failure::Failed(format!("couldn't load events: {}", e)))?;
+            execute(&mut planner, command, format)
+        });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Invalid(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::from(2)
+        }
+        Err(Failure::Failed(message)) => {
+            eprintln!("error: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn execute(planner: &mut Planner, command: Command, format: OutputFormat) -> Result<(), Failure> {
+    match command {
+        Command::Add(args) => {
+            let event = event_from_args(args)?;
+            let id = planner
+                .create_event(event)
+                .map_err(|e| Failure::Failed(format!("couldn't save the event: {}", e)))?;
+            println!("{}", id);
+        }
+        Command::List(args) => {
+            let now = Local::now().naive_local();
+            let filter = match args.filter {
+                Some(filter) => Some(Filter::parse(&filter, now).map_err(Failure::Invalid)?),
+                None => None,
+            };
+            let events = planner.query(filter.as_ref(), args.sort, args.limit);
+            print_result(output::print_events(format, &events))?;
+        }
+        Command::Today => {
+            let occurrences = planner.occurrences_on(Local::now().date_naive());
+            print_result(output::print_occurrences(format, &occurrences))?;
+        }
+        Command::Show { id } => {
+            let event = find_event(planner, id)?.clone();
+            print_result(output::print_events(format, &[event]))?;
+        }
+        Command::Search { query, limit } => {
+            let mut hits = planner.search(&query.join(" "));
+            hits.truncate(limit.unwrap_or(usize::MAX));
+            print_result(output::print_search(format, &hits, output::color_enabled()))?;
+        }
+        Command::Agenda(args) => {
+            let now = Local::now().naive_local();
+            let (first, last) = range_dates(agenda_range(args)?, now.date())?;
+            let agenda = planner.agenda(first, last, now);
+            match format {
+                OutputFormat::Text => agenda.display(),
+                _ => print_result(output::print_occurrences(format, &agenda.occurrences()))?,
+            }
+        }
+        Command::Calendar { week, date, width } => {
+            let today = Local::now().date_naive();
+            let date = match date {
+                Some(date) => parse_date(&date, None)?,
+                None => today,
+            };
+            let mut canvas = Canvas::detect();
+            canvas.width = width.unwrap_or(canvas.width);
+            if week {
+                println!("{}", calendar::render_week(planner, date, today, &canvas));
+            } else {
+                println!("{}", calendar::render_month(planner, date, today, &canvas));
+            }
+        }
+        Command::Edit(args) => {
+            let id = args.id;
+            edit_event(find_event(planner, id)?, args)?;
+            save_event(planner, id)?;
+            let event = find_event(planner, id)?.clone();
+            print_result(output::print_events(format, &[event]))?;
+        }
+        Command::Done { id, on, series, undo } => {
+            let event = find_event(planner, id)?;
+            let tz = event.get_time_zone();
+            let date = match on {
+                Some(date) => Some(parse_date(&date, tz)?),
+                None if event.is_recurring() && !series => Some(time_zone::now_in(tz).date()),
+                None => None,
+            };
+            let state = if undo { "not completed" } else { "completed" };
+            match date {
+                Some(date) => {
+                    let day_start = date.and_hms_opt(0, 0, 0).unwrap();
+                    let day_end = day_start + Days::new(1);
+                    let Some(original_start) =
+                        occurrence::original_starts(event, day_start, day_end).into_iter().next()
+                    else {
+                        return Err(Failure::Failed(format!("event {} doesn't occur on {}", id, date)));
+                    };
+                    event.set_occurrence_completed(original_start, !undo);
+                    save_event(planner, id)?;
+                    println!("Event {} on {} marked {}.", id, date, state);
+                }
+                None => {
+                    event.set_completed(!undo);
+                    save_event(planner, id)?;
+                    println!("Event {} marked {}.", id, state);
+                }
+            }
+        }
+        Command::Import(args) => {
+            let file_type = match args.file_type {
+                Some(file_type) => file_type,
+                None => ExchangeFormat::from_path(&args.file).map_err(Failure::Invalid)?,
+            };
+            if !args.delimiter.is_ascii() {
+                return Err(Failure::Invalid("the delimiter must be a single ASCII character".to_string()));
+            }
+            let csv_options = CsvOptions {
+                mappings: args.mappings,
+                date_formats: args.date_formats,
+                delimiter: args.delimiter as u8,
+                has_header: !args.no_header,
+                zone: match &args.tz {
+                    Some(name) => parse_zone(name)?,
+                    None => time_zone::local_zone(),
+                },
+            };
+            let parsed = exchange::read_file(&args.file, file_type, &csv_options)
+                .map_err(|e| Failure::Failed(format!("couldn't read {}: {}", args.file, e)))?;
+            let report = planner.import_events(parsed, args.update);
+            report.display();
+            if report.failed > 0 {
+                return Err(Failure::Failed(format!("{} event(s) couldn't be saved", report.failed)));
+            }
+        }
+        Command::Export(args) => {
+            let file_type = match (args.file_type, &args.file) {
+                (Some(file_type), _) => file_type,
+                (None, Some(file)) => ExchangeFormat::from_path(file).map_err(Failure::Invalid)?,
+                (None, None) => ExchangeFormat::ICalendar,
+            };
+            let now = Local::now().naive_local();
+            let filter = match args.filter {
+                Some(filter) => Some(Filter::parse(&filter, now).map_err(Failure::Invalid)?),
+                None => None,
+            };
+            let events = planner.query(filter.as_ref(), SortKey::default(), None);
+            exchange::write_file(args.file.as_deref(), file_type, &events)
+                .map_err(|e| Failure::Failed(format!("couldn't write events: {}", e)))?;
+            if let Some(file) = &args.file {
+                println!("Exported {} event(s) to {}.", events.len(), file);
+            }
+        }
+        Command::Report(args) => {
+            let file_type = match (args.file_type, &args.file) {
+                (Some(file_type), _) => file_type,
+                (None, Some(file)) => ReportFormat::from_path(file).map_err(Failure::Invalid)?,
+                (None, None) => ReportFormat::Markdown,
+            };
+            let now = Local::now().naive_local();
+            let (first, last) = range_dates(agenda_range(args.range)?, now.date())?;
+            let text = report::render(planner, file_type, first, last, now);
+            match &args.file {
+                Some(file) => {
+                    atomic_write(file, |out| out.write_all(text.as_bytes()))
+                        .map_err(|e| Failure::Failed(format!("couldn't write {}: {}", file, e)))?;
+                    println!("Wrote the agenda for {} to {} to {}.", first, last, file);
+                }
+                None => print!("{}", text),
+            }
+        }
+        Command::Migrate { dry_run } => migrate(planner, dry_run)?,
+        Command::Delete { id } => {
+            find_event(planner, id)?;
+            planner
+                .delete_event(id)
+                .map_err(|e| Failure::Failed(format!("couldn't delete event {}: {}", id, e)))?;
+            println!("Event {} deleted.", id);
+        }
+        Command::Tui => {
+            tui::run(planner).map_err(|e| Failure::Failed(format!("terminal error: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+fn migrate(planner: &mut Planner, dry_run: bool) -> Result<(), Failure> {
+    match planner.migrate(dry_run) {
+        Ok(Some(report)) => println!("{}", report),
+        Ok(None) => println!("The planner is already in the current format."),
+        Err(e) => return Err(Failure::Failed(format!("couldn't migrate the planner: {}", e))),
+    }
+    Ok(())
+}
+
+fn find_event(planner: &mut Planner, id: usize) -> Result<&mut Event, Failure> {
+    planner
+        .find_event_mut(id)
+        .ok_or_else(|| Failure::Failed(format!("no event with id {}", id)))
+}
+
+fn print_result(result: std::io::Result<()>) -> Result<(), Failure> {
+    result.map_err(|e| Failure::Failed(format!("couldn't write output: {}", e)))
+}
+
+fn save_event(planner: &mut Planner, id: usize) -> Result<(), Failure> {
+    planner
+        .update_event(id)
+        .map_err(|e| Failure::Failed(format!("couldn't save event {}: {}", id, e)))
+}
+
+fn agenda_range(args: AgendaArgs) -> Result<AgendaRange, Failure> {
+    if let Some(days) = args.days {
+        return Ok(AgendaRange::Days(days));
+    }
+    if let Some(month) = args.month {
+        let now = time_zone::now_in(None);
+        return date_parser::parse_month(&month, now, None)
+            .map(AgendaRange::Month)
+            .map_err(Failure::Invalid);
+    }
+    match (args.from, args.to) {
+        (Some(first), Some(last)) => {
+            Ok(AgendaRange::Between(parse_date(&first, None)?, parse_date(&last, None)?))
+        }
+        _ => Ok(AgendaRange::Week),
+    }
+}
+
+fn range_dates(range: AgendaRange, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), Failure> {
+    let (first, last) = range
+        .dates(today)
+        .ok_or_else(|| Failure::Invalid("the range runs past the last supported date".to_string()))?;
+    if last < first {
+        return Err(Failure::Invalid("the range ends before it starts".to_string()));
+    }
+    Ok((first, last))
+}
+
+fn event_from_args(args: AddArgs) -> Result<Event, Failure> {
+    let tz = match &args.tz {
+        Some(name) => parse_zone(name)?,
+        None => time_zone::local_zone(),
+    };
+    let start = parse_time(&args.start, tz)?;
+    let all_day = args.all_day || matches!(start, TimeInput::Date(_));
+
+    let (start_time, end_time) = if all_day {
+        let start_date = date_of(start);
+        let end_date = match &args.end {
+            Some(end) => parse_date(end, None)?,
+            None => start_date,
+        };
+        (start_date.and_hms_opt(0, 0, 0).unwrap(), end_date.and_hms_opt(0, 0, 0).unwrap())
+    } else {
+        let start_time = require_time(start, "start")?;
+        let end_time = match &args.end {
+            Some(end) => parse_end(end, start_time, tz)?,
+            None => start_time,
+        };
+        (start_time, end_time)
+    };
+    if end_time < start_time {
+        return Err(Failure::Invalid("the event ends before it starts".to_string()));
+    }
+
+    let mut event = Event::new(
+        0,
+        args.name,
+        start_time,
+        end_time,
+        args.priority,
+        args.repeat,
+        args.note,
+        args.completed,
+    );
+    if all_day {
+        event.set_all_day(true);
+    } else {
+        event.set_time_zone(tz);
+    }
+    Ok(event)
+}
+
+fn edit_event(event: &mut Event, args: EditArgs) -> Result<(), Failure> {
+    if let Some(name) = args.tz {
+        event.set_time_zone(parse_zone(&name)?);
+    }
+    if let Some(all_day) = args.all_day {
+        event.set_all_day(all_day);
+    }
+    if event.is_all_day() && event.get_time_zone().is_some() {
+        return Err(Failure::Invalid("all-day events can't have a time zone".to_string()));
+    }
+    let tz = event.get_time_zone();
+    if let Some(start) = args.start {
+        let start = parse_time(&start, tz)?;
+        if event.is_all_day() {
+            event.set_start_time(date_of(start).and_hms_opt(0, 0, 0).unwrap());
+        } else {
+            event.set_start_time(require_time(start, "start")?);
+        }
+    }
+    if let Some(end) = args.end {
+        if event.is_all_day() {
+            event.set_end_time(parse_date(&end, None)?.and_hms_opt(0, 0, 0).unwrap());
+        } else {
+            event.set_end_time(parse_end(&end, event.get_start_time(), tz)?);
+        }
+    }
+    if event.get_end_instant() < event.get_start_instant() {
+        return Err(Failure::Invalid("the event ends before it starts".to_string()));
+    }
+
+    if let Some(name) = args.name {
+        event.set_name(name);
+    }
+    if let Some(priority) = args.priority {
+        event.set_priority(priority);
+    }
+    if let Some(reoccurance) = args.repeat {
+        event.set_reoccurance(reoccurance);
+    }
+    if let Some(note) = args.note {
+        event.set_note(note);
+    }
+    if let Some(completed) = args.completed {
+        event.set_completed(completed);
+    }
+    Ok(())
+}
+
+fn parse_zone(name: &str) -> Result<Option<Tz>, Failure> {
+    if name.eq_ignore_ascii_case("floating") {
+        return Ok(None);
+    }
+    name.parse::<Tz>()
+        .map(Some)
+        .map_err(|e| Failure::Invalid(format!("unknown time zone: {}", e)))
+}
+
+fn parse_time(input: &str, tz: Option<Tz>) -> Result<TimeInput, Failure> {
+    date_parser::parse(input, time_zone::now_in(tz), tz).map_err(Failure::Invalid)
+}
+
+fn parse_end(input: &str, start: NaiveDateTime, tz: Option<Tz>) -> Result<NaiveDateTime, Failure> {
+    let end = date_parser::parse_end(input, start, time_zone::now_in(tz), tz).map_err(Failure::Invalid)?;
+    require_time(end, "end")
+}
+
+fn parse_date(input: &str, tz: Option<Tz>) -> Result<NaiveDate, Failure> {
+    parse_time(input, tz).map(date_of)
+}
+
+fn date_of(time: TimeInput) -> NaiveDate {
+    match time {
+        TimeInput::Date(date) => date,
+        TimeInput::At(dt) => dt.date(),
+    }
+}
+
+fn require_time(time: TimeInput, which: &str) -> Result<NaiveDateTime, Failure> {
+    match time {
+        TimeInput::At(dt) => Ok(dt),
+        TimeInput::Date(_) => Err(Failure::Invalid(format!(
+            "the {} needs a time of day, or pass --all-day",
+            which
+        ))),
+    }
+}
@@ -1,2 +1,7 @@
+pub mod calendar;
 pub mod cli;
+pub mod commands;
+pub mod output;
+pub mod report;
+pub mod tui;
 pub mod ui_helpers;
\ No newline at end of file
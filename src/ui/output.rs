@@ -0,0 +1,214 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+
+use ratatui::crossterm::style::Stylize;
+use serde::Serialize;
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::Occurrence;
+use crate::backend::enums::output_format::OutputFormat;
+use crate::backend::search::SearchHit;
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+/// Characters of a note shown around its first match in search results
+const SNIPPET_WIDTH: usize = 60;
+const SNIPPET_LEAD: usize = 20;
+
+/// Column names of `Record`, in order, for the CSV/TSV header.
+const FIELDS: [&str; 11] = [
+    "id", "uid", "name", "start", "end", "time_zone", "all_day", "priority", "recurrence", "note", "completed",
+];
+
+/// One event, or one occurrence of a recurring event, in machine-readable
+/// output. This schema is stable: fields may be added at the end but are
+/// never renamed, removed or reordered.
+///
+/// - `id`: the event's id in this planner, shared by all its occurrences
+/// - `uid`: the event's UUID, stable across planners and exports
+/// - `name`: the occurrence's name (renamed occurrences differ from the event)
+/// - `start`, `end`: `YYYY-MM-DDTHH:MM:SS` in the viewer's zone; all-day
+///   events run from midnight of the first to midnight of the last date
+/// - `time_zone`: IANA zone name, or null (empty in CSV/TSV) for floating times
+/// - `all_day`: `true` or `false`
+/// - `priority`: `High`, `Medium` or `Low`
+/// - `recurrence`: `None`, `Daily`, `Weekly`, `Fornite`, `Monthly`, `Yearly`
+///   or an `RRULE:...` string
+/// - `note`: free text, may contain newlines
+/// - `completed`: `true` or `false`, per occurrence for recurring events
+#[derive(Serialize)]
+pub struct Record {
+    pub id: usize,
+    pub uid: String,
+    pub name: String,
+    pub start: String,
+    pub end: String,
+    pub time_zone: Option<String>,
+    pub all_day: bool,
+    pub priority: String,
+    pub recurrence: String,
+    pub note: String,
+    pub completed: bool,
+}
+
+impl From<&Occurrence> for Record {
+    fn from(occurrence: &Occurrence) -> Self {
+        let event = &occurrence.event;
+        Record {
+            id: *event.get_id(),
+            uid: event.get_uid().to_string(),
+            name: occurrence.name.clone(),
+            start: occurrence.start.format(TIME_FORMAT).to_string(),
+            end: occurrence.end.format(TIME_FORMAT).to_string(),
+            time_zone: event.get_time_zone().map(|tz| tz.name().to_string()),
+            all_day: event.is_all_day(),
+            priority: format!("{:?}", event.get_priority()),
+            recurrence: event.get_reoccurance().to_string(),
+            note: event.get_note().clone(),
+            completed: occurrence.completed,
+        }
+    }
+}
+
+impl From<&Event> for Record {
+    fn from(event: &Event) -> Self {
+        Record {
+            id: *event.get_id(),
+            uid: event.get_uid().to_string(),
+            name: event.get_name().clone(),
+            start: event.local_start().format(TIME_FORMAT).to_string(),
+            end: event.local_end().format(TIME_FORMAT).to_string(),
+            time_zone: event.get_time_zone().map(|tz| tz.name().to_string()),
+            all_day: event.is_all_day(),
+            priority: format!("{:?}", event.get_priority()),
+            recurrence: event.get_reoccurance().to_string(),
+            note: event.get_note().clone(),
+            completed: event.get_completed(),
+        }
+    }
+}
+
+pub fn print_events(format: OutputFormat, events: &[Event]) -> io::Result<()> {
+    if format == OutputFormat::Text {
+        if events.is_empty() {
+            println!("No events found.");
+        }
+        events.iter().for_each(Event::display);
+        return Ok(());
+    }
+    write_records(format, events.iter().map(Record::from), io::stdout().lock())
+}
+
+pub fn print_occurrences(format: OutputFormat, occurrences: &[Occurrence]) -> io::Result<()> {
+    if format == OutputFormat::Text {
+        if occurrences.is_empty() {
+            println!("No events found.");
+        }
+        occurrences.iter().for_each(Occurrence::display);
+        return Ok(());
+    }
+    write_records(format, occurrences.iter().map(Record::from), io::stdout().lock())
+}
+
+/// Whether to style text output: only on a terminal, and not when
+/// `NO_COLOR` is set.
+pub fn color_enabled() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+/// Marks `ranges` (in characters) of `text`, in bold yellow with `color`
+/// and in brackets without.
+fn highlight(text: &str, ranges: &[Range<usize>], color: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut position = 0;
+    for range in ranges {
+        out.extend(&chars[position..range.start]);
+        let matched: String = chars[range.clone()].iter().collect();
+        if color {
+            out.push_str(&matched.bold().yellow().to_string());
+        } else {
+            out.push_str(&format!("[{}]", matched));
+        }
+        position = range.end;
+    }
+    out.extend(&chars[position..]);
+    out
+}
+
+/// A window of the note around its first match, on one line.
+fn note_snippet(note: &str, ranges: &[Range<usize>], color: bool) -> String {
+    let flat: String = note.chars().map(|c| if c == '\n' || c == '\r' { ' ' } else { c }).collect();
+    let length = flat.chars().count();
+    let start = ranges[0].start.saturating_sub(SNIPPET_LEAD);
+    let end = (start + SNIPPET_WIDTH).min(length);
+    let window: String = flat.chars().skip(start).take(end - start).collect();
+    let shifted: Vec<Range<usize>> = ranges
+        .iter()
+        .filter(|r| r.start >= start && r.end <= end)
+        .map(|r| r.start - start..r.end - start)
+        .collect();
+    let before = if start > 0 { "..." } else { "" };
+    let after = if end < length { "..." } else { "" };
+    format!("{}{}{}", before, highlight(&window, &shifted, color), after)
+}
+
+/// Search results in rank order. Text output is one line per event with the
+/// matches highlighted, plus the matching part of the note.
+pub fn print_search(format: OutputFormat, hits: &[SearchHit], color: bool) -> io::Result<()> {
+    if format != OutputFormat::Text {
+        return write_records(format, hits.iter().map(|hit| Record::from(&hit.event)), io::stdout().lock());
+    }
+    if hits.is_empty() {
+        println!("No events found.");
+    }
+    for hit in hits {
+        let event = &hit.event;
+        let when = if event.is_all_day() { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M" };
+        println!(
+            "{:>4}  {}  ({})",
+            event.get_id(),
+            highlight(event.get_name(), &hit.name_matches, color),
+            event.local_start().format(when)
+        );
+        if !hit.note_matches.is_empty() {
+            println!("      note: {}", note_snippet(event.get_note(), &hit.note_matches, color));
+        }
+    }
+    Ok(())
+}
+
+/// Writes `records` as a JSON array, JSON Lines, or CSV/TSV with a header
+/// row (written even when there are no records).
+fn write_records(
+    format: OutputFormat,
+    records: impl Iterator<Item = Record>,
+    mut out: impl Write,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text | OutputFormat::Json => {
+            let records: Vec<Record> = records.collect();
+            serde_json::to_writer_pretty(&mut out, &records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::JsonLines => {
+            for record in records {
+                serde_json::to_writer(&mut out, &record)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let delimiter = if format == OutputFormat::Tsv { b'\t' } else { b',' };
+            let mut writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .has_headers(false)
+                .from_writer(out);
+            writer.write_record(FIELDS).map_err(io::Error::other)?;
+            for record in records {
+                writer.serialize(record).map_err(io::Error::other)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
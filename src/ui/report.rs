@@ -0,0 +1,232 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime};
+
+use crate::backend::classes::occurrence::Occurrence;
+use crate::backend::classes::planner::Planner;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::report_format::ReportFormat;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const DAY_FORMAT: &str = "%a %-d %b";
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.5em; }
+table { border-collapse: collapse; width: 100%; table-layout: fixed; }
+th, td { border: 1px solid #ccc; padding: 4px; vertical-align: top; }
+th { background: #f4f4f4; }
+td { height: 6em; }
+td.outside { background: #fafafa; color: #aaa; }
+td.today { outline: 2px solid #1565c0; outline-offset: -2px; }
+.day { font-weight: bold; font-size: 0.9em; }
+ul { list-style: none; margin: 0; padding: 0; }
+li { margin: 2px 0; padding: 1px 4px; border-left: 4px solid; font-size: 0.85em; }
+.high { border-color: #c62828; background: #fdecea; }
+.medium { border-color: #ef8f00; background: #fff4e0; }
+.low { border-color: #2e7d32; background: #e8f5e9; }
+.done { text-decoration: line-through; opacity: 0.6; }
+.overdue { font-weight: bold; }
+.legend span { border-left: 4px solid; padding: 0 6px; margin-right: 6px; }
+";
+
+/// Writes the occurrences from `first` to `last` (both included) as
+/// `format`, with incomplete items due before `now` listed as overdue.
+pub fn render(
+    planner: &Planner,
+    format: ReportFormat,
+    first: NaiveDate,
+    last: NaiveDate,
+    now: NaiveDateTime,
+) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(planner, first, last, now),
+        ReportFormat::Html => render_html(planner, first, last, now),
+    }
+}
+
+fn title(first: NaiveDate, last: NaiveDate) -> String {
+    if first == last {
+        format!("Agenda for {}", first.format("%A %Y-%m-%d"))
+    } else {
+        format!("Agenda, {} to {}", first.format("%A %Y-%m-%d"), last.format("%A %Y-%m-%d"))
+    }
+}
+
+fn priority_class(priority: &Priority) -> &'static str {
+    match priority {
+        Priority::High => "high",
+        Priority::Medium => "medium",
+        Priority::Low => "low",
+    }
+}
+
+/// When an occurrence happens, with its date when `with_date` is set or it
+/// runs over several days.
+fn time_label(occurrence: &Occurrence, with_date: bool) -> String {
+    let (start, end) = (occurrence.start, occurrence.end);
+    let same_day = start.date() == end.date();
+    let date = |at: NaiveDateTime| at.format(DAY_FORMAT).to_string();
+    if occurrence.event.is_all_day() {
+        return match (same_day, with_date) {
+            (true, false) => "all day".to_string(),
+            (true, true) => format!("{}, all day", date(start)),
+            (false, _) => format!("all day, {} to {}", date(start), date(end)),
+        };
+    }
+    let times = if !same_day {
+        format!("{} {} to {} {}", date(start), start.format("%H:%M"), date(end), end.format("%H:%M"))
+    } else if start == end {
+        start.format("%H:%M").to_string()
+    } else {
+        format!("{}-{}", start.format("%H:%M"), end.format("%H:%M"))
+    };
+    if with_date && same_day { format!("{} {}", date(start), times) } else { times }
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn markdown_item(occurrence: &Occurrence, with_date: bool) -> String {
+    let check = if occurrence.completed { "x" } else { " " };
+    let priority = match occurrence.event.get_priority() {
+        Priority::High => " *(high priority)*",
+        Priority::Medium => "",
+        Priority::Low => " *(low priority)*",
+    };
+    let mut item = format!(
+        "- [{}] {} {}{}",
+        check,
+        time_label(occurrence, with_date),
+        escape_markdown(&occurrence.name),
+        priority
+    );
+    for line in occurrence.event.get_note().lines().filter(|l| !l.trim().is_empty()) {
+        item.push_str(&format!("\n  > {}", escape_markdown(line.trim())));
+    }
+    item
+}
+
+/// Checkbox lists grouped by day, overdue items first. Days with nothing
+/// on them are left out.
+fn render_markdown(planner: &Planner, first: NaiveDate, last: NaiveDate, now: NaiveDateTime) -> String {
+    let agenda = planner.agenda(first, last, now);
+    let mut sections = vec![format!("# {}", title(first, last))];
+    if agenda.is_empty() {
+        sections.push("_No events._".to_string());
+    }
+    if !agenda.overdue.is_empty() {
+        let items: Vec<String> = agenda.overdue.iter().map(|o| markdown_item(o, true)).collect();
+        sections.push(format!("## Overdue\n\n{}", items.join("\n")));
+    }
+    for (date, occurrences) in agenda.days.iter().filter(|(_, o)| !o.is_empty()) {
+        let items: Vec<String> = occurrences.iter().map(|o| markdown_item(o, false)).collect();
+        sections.push(format!("## {}\n\n{}", date.format("%A %Y-%m-%d"), items.join("\n")));
+    }
+    sections.join("\n\n") + "\n"
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A list item colored by priority, with the note as its tooltip.
+fn html_item(occurrence: &Occurrence, with_date: bool, now: NaiveDateTime) -> String {
+    let mut classes = vec![priority_class(occurrence.event.get_priority())];
+    let due = if occurrence.event.is_all_day() { occurrence.end + Days::new(1) } else { occurrence.end };
+    if occurrence.completed {
+        classes.push("done");
+    } else if due <= now {
+        classes.push("overdue");
+    }
+    let note = occurrence.event.get_note().trim();
+    let tooltip = if note.is_empty() { String::new() } else { format!(" title=\"{}\"", escape_html(note)) };
+    format!(
+        "<li class=\"{}\"{}>{} {}</li>",
+        classes.join(" "),
+        tooltip,
+        escape_html(&time_label(occurrence, with_date)),
+        escape_html(&occurrence.name)
+    )
+}
+
+/// A standalone page with the range drawn as a calendar table of whole
+/// weeks, events colored by priority, and overdue items listed above it.
+fn render_html(planner: &Planner, first: NaiveDate, last: NaiveDate, now: NaiveDateTime) -> String {
+    let title = title(first, last);
+    let overdue = planner.agenda(first, last, now).overdue;
+    let grid_start = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    let grid_end = last + Days::new(6 - last.weekday().num_days_from_monday() as u64);
+    let days = planner.occurrences_by_day(grid_start, grid_end);
+
+    let mut lines = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html lang=\"en\">".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        format!("<title>{}</title>", escape_html(&title)),
+        format!("<style>\n{}</style>", STYLE),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        format!("<h1>{}</h1>", escape_html(&title)),
+        "<p class=\"legend\">Priority: <span class=\"high\">high</span><span class=\"medium\">medium</span>\
+         <span class=\"low\">low</span></p>"
+            .to_string(),
+    ];
+    if !overdue.is_empty() {
+        lines.push("<h2>Overdue</h2>".to_string());
+        lines.push("<ul>".to_string());
+        lines.extend(overdue.iter().map(|o| html_item(o, true, now)));
+        lines.push("</ul>".to_string());
+    }
+
+    lines.push("<table>".to_string());
+    let headers: String = WEEKDAYS.iter().map(|d| format!("<th>{}</th>", d)).collect();
+    lines.push(format!("<thead><tr>{}</tr></thead>", headers));
+    lines.push("<tbody>".to_string());
+    for week in days.chunks(7) {
+        lines.push("<tr>".to_string());
+        for (date, occurrences) in week {
+            let label = if date.day() == 1 || *date == grid_start {
+                date.format("%-d %b").to_string()
+            } else {
+                date.day().to_string()
+            };
+            if *date < first || *date > last {
+                lines.push(format!("<td class=\"outside\"><div class=\"day\">{}</div></td>", label));
+                continue;
+            }
+            let class = if *date == now.date() { " class=\"today\"" } else { "" };
+            lines.push(format!("<td{}><div class=\"day\">{}</div>", class, label));
+            if !occurrences.is_empty() {
+                lines.push("<ul>".to_string());
+                lines.extend(occurrences.iter().map(|o| html_item(o, false, now)));
+                lines.push("</ul>".to_string());
+            }
+            lines.push("</td>".to_string());
+        }
+        lines.push("</tr>".to_string());
+    }
+    lines.push("</tbody>".to_string());
+    lines.push("</table>".to_string());
+    lines.push("</body>".to_string());
+    lines.push("</html>".to_string());
+    lines.join("\n") + "\n"
+}
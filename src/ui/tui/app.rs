@@ -0,0 +1,271 @@
+use std::collections::HashSet;
+use std::io;
+
+use chrono::{Datelike, Days, Duration, Local, Months, NaiveDate};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::widgets::ListState;
+
+use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::Occurrence;
+use crate::backend::classes::planner::Planner;
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::ui::tui::form::{EventForm, FormAction};
+use crate::ui::tui::view;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Calendar,
+    Agenda,
+}
+
+pub struct App<'a> {
+    pub planner: &'a mut Planner,
+    pub today: NaiveDate,
+    pub selected_date: NaiveDate,
+    pub focus: Focus,
+    /// Occurrences on `selected_date`
+    pub agenda: Vec<Occurrence>,
+    pub agenda_state: ListState,
+    /// Days of the shown month that have at least one occurrence
+    pub busy_days: HashSet<NaiveDate>,
+    pub form: Option<EventForm>,
+    pub confirm_delete: bool,
+    pub status: String,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(planner: &'a mut Planner) -> Self {
+        let today = Local::now().date_naive();
+        let mut app = Self {
+            planner,
+            today,
+            selected_date: today,
+            focus: Focus::Calendar,
+            agenda: Vec::new(),
+            agenda_state: ListState::default(),
+            busy_days: HashSet::new(),
+            form: None,
+            confirm_delete: false,
+            status: String::new(),
+            quit: false,
+        };
+        app.refresh();
+        app
+    }
+
+    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| view::draw(frame, self))?;
+            if let TermEvent::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+            {
+                self.handle_key(key);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn selected_occurrence(&self) -> Option<&Occurrence> {
+        self.agenda.get(self.agenda_state.selected()?)
+    }
+
+    /// Reloads the agenda and the calendar markers after the date or the
+    /// events changed.
+    fn refresh(&mut self) {
+        self.agenda = self.planner.occurrences_on(self.selected_date);
+        let selected = match self.agenda_state.selected() {
+            _ if self.agenda.is_empty() => None,
+            Some(index) => Some(index.min(self.agenda.len() - 1)),
+            None => Some(0),
+        };
+        self.agenda_state.select(selected);
+
+        let month_start = self.selected_date.with_day(1).unwrap();
+        let month_end = month_start + Months::new(1);
+        let occurrences = self
+            .planner
+            .occurrences_between(month_start.and_hms_opt(0, 0, 0).unwrap(), month_end.and_hms_opt(0, 0, 0).unwrap());
+        self.busy_days.clear();
+        for occurrence in occurrences {
+            // Timed occurrences ending at midnight don't reach the next day
+            let end = if occurrence.event.is_all_day() || occurrence.end == occurrence.start {
+                occurrence.end
+            } else {
+                occurrence.end - Duration::seconds(1)
+            };
+            let mut day = occurrence.start.date().max(month_start);
+            let last = end.date().min(month_end);
+            loop {
+                self.busy_days.insert(day);
+                if day >= last {
+                    break;
+                }
+                day = day + Days::new(1);
+            }
+        }
+    }
+
+    fn select_date(&mut self, date: NaiveDate) {
+        if date != self.selected_date {
+            self.selected_date = date;
+            self.agenda_state.select(None);
+            self.refresh();
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(form) = &mut self.form {
+            match form.handle_key(key) {
+                FormAction::Continue => {}
+                FormAction::Cancel => self.form = None,
+                FormAction::Save => self.save_form(),
+            }
+            return;
+        }
+        if self.confirm_delete {
+            self.confirm_delete = false;
+            if key.code == KeyCode::Char('y') {
+                self.delete_selected();
+            } else {
+                self.status = "Delete cancelled.".to_string();
+            }
+            return;
+        }
+
+        self.status.clear();
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Char('n') => self.form = Some(EventForm::for_new(self.selected_date)),
+            KeyCode::Char('t') => self.select_date(self.today),
+            KeyCode::PageUp | KeyCode::Char('<') => self.select_date(self.selected_date - Months::new(1)),
+            KeyCode::PageDown | KeyCode::Char('>') => self.select_date(self.selected_date + Months::new(1)),
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Calendar => Focus::Agenda,
+                    Focus::Agenda => Focus::Calendar,
+                }
+            }
+            _ => match self.focus {
+                Focus::Calendar => self.handle_calendar_key(key),
+                Focus::Agenda => self.handle_agenda_key(key),
+            },
+        }
+    }
+
+    fn handle_calendar_key(&mut self, key: KeyEvent) {
+        let date = self.selected_date;
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => self.select_date(date - Days::new(1)),
+            KeyCode::Right | KeyCode::Char('l') => self.select_date(date + Days::new(1)),
+            KeyCode::Up | KeyCode::Char('k') => self.select_date(date - Days::new(7)),
+            KeyCode::Down | KeyCode::Char('j') => self.select_date(date + Days::new(7)),
+            KeyCode::Enter => self.focus = Focus::Agenda,
+            _ => {}
+        }
+    }
+
+    fn handle_agenda_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.agenda_state.select_previous(),
+            KeyCode::Down | KeyCode::Char('j')
+                if self.agenda_state.selected().is_some_and(|i| i + 1 < self.agenda.len()) =>
+            {
+                self.agenda_state.select_next();
+            }
+            KeyCode::Esc => self.focus = Focus::Calendar,
+            KeyCode::Char(' ') | KeyCode::Char('x') => self.toggle_selected(),
+            KeyCode::Enter | KeyCode::Char('e') => {
+                if let Some(occurrence) = self.selected_occurrence() {
+                    self.form = Some(EventForm::for_event(&occurrence.event));
+                }
+            }
+            KeyCode::Char('d') if self.selected_occurrence().is_some() => {
+                self.confirm_delete = true;
+            }
+            _ => {}
+        }
+    }
+
+    /// Toggles the selected occurrence, or the whole event if it doesn't repeat.
+    fn toggle_selected(&mut self) {
+        let Some(occurrence) = self.selected_occurrence() else {
+            return;
+        };
+        let id = *occurrence.event.get_id();
+        let (original_start, completed) = (occurrence.original_start, occurrence.completed);
+        if let Some(event) = self.planner.find_event_mut(id) {
+            if event.is_recurring() {
+                event.set_occurrence_completed(original_start, !completed);
+            } else {
+                event.set_completed(!completed);
+            }
+        }
+        self.save(id);
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(occurrence) = self.selected_occurrence() else {
+            return;
+        };
+        let id = *occurrence.event.get_id();
+        match self.planner.delete_event(id) {
+            Ok(Some(event)) => self.status = format!("Deleted '{}'.", event.get_name()),
+            Ok(None) => {}
+            Err(e) => self.status = format!("Failed to delete: {}", e),
+        }
+        self.refresh();
+    }
+
+    fn save_form(&mut self) {
+        let Some(form) = &mut self.form else {
+            return;
+        };
+        let result = match form.event_id {
+            // Edits a copy so a failed save leaves the planner as it was
+            Some(id) => match self.planner.find_event_mut(id).map(|event| event.clone()) {
+                Some(mut event) => form.apply(&mut event).and_then(|_| {
+                    match self.planner.replace_event(event) {
+                        Ok(()) => Ok("Saved."),
+                        Err(e) => Err(format!("Failed to save: {}", e)),
+                    }
+                }),
+                None => Err(format!("Event {} no longer exists", id)),
+            },
+            None => {
+                let mut event = Event::new(
+                    0,
+                    String::new(),
+                    self.selected_date.and_hms_opt(0, 0, 0).unwrap(),
+                    self.selected_date.and_hms_opt(0, 0, 0).unwrap(),
+                    Priority::Medium,
+                    Reoccurance::None,
+                    String::new(),
+                    false,
+                );
+                form.apply(&mut event).and_then(|_| match self.planner.create_event(event) {
+                    Ok(_) => Ok("Event created."),
+                    Err(e) => Err(format!("Failed to save: {}", e)),
+                })
+            }
+        };
+        match result {
+            Ok(status) => {
+                self.form = None;
+                self.status = status.to_string();
+                self.refresh();
+            }
+            Err(e) => form.error = Some(e),
+        }
+    }
+
+    fn save(&mut self, id: usize) {
+        self.status = match self.planner.update_event(id) {
+            Ok(()) => "Saved.".to_string(),
+            Err(e) => format!("Failed to save: {}", e),
+        };
+        self.refresh();
+    }
+}
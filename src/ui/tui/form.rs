@@ -0,0 +1,147 @@
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+use crate::backend::classes::event::Event;
+use crate::backend::date_parser::{self, TimeInput};
+use crate::backend::enums::priority::Priority;
+use crate::backend::enums::reoccurance::Reoccurance;
+use crate::backend::time_zone;
+
+pub const LABELS: [&str; 9] = [
+    "Name", "Start", "End", "Time zone", "All day", "Priority", "Recurrence", "Note", "Completed",
+];
+const START: usize = 1;
+const END: usize = 2;
+const TIME_ZONE: usize = 3;
+const ALL_DAY: usize = 4;
+const COMPLETED: usize = 8;
+
+pub enum FormAction {
+    Continue,
+    Save,
+    Cancel,
+}
+
+/// Editable copy of an event's fields. Times are typed like in the menu
+/// (`tomorrow 3pm`, `+45m` for the end, ...) and only parsed on save.
+pub struct EventForm {
+    /// The event being edited, or `None` for a new one
+    pub event_id: Option<usize>,
+    pub values: [String; 9],
+    pub selected: usize,
+    pub error: Option<String>,
+}
+
+impl EventForm {
+    pub fn for_event(event: &Event) -> Self {
+        let time_format = if event.is_all_day() { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M" };
+        Self {
+            event_id: Some(*event.get_id()),
+            values: [
+                event.get_name().clone(),
+                event.get_start_time().format(time_format).to_string(),
+                event.get_end_time().format(time_format).to_string(),
+                zone_value(event.get_time_zone()),
+                yes_no(event.is_all_day()),
+                format!("{:?}", event.get_priority()),
+                event.get_reoccurance().to_string(),
+                event.get_note().clone(),
+                yes_no(event.get_completed()),
+            ],
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// A blank one-hour event at 9am on `date`.
+    pub fn for_new(date: NaiveDate) -> Self {
+        Self {
+            event_id: None,
+            values: [
+                String::new(),
+                format!("{} 09:00", date.format("%Y-%m-%d")),
+                "+1h".to_string(),
+                zone_value(time_zone::local_zone()),
+                yes_no(false),
+                format!("{:?}", Priority::Medium),
+                Reoccurance::None.to_string(),
+                String::new(),
+                yes_no(false),
+            ],
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormAction {
+        match key.code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Enter => return FormAction::Save,
+            KeyCode::Up | KeyCode::BackTab => self.selected = (self.selected + LABELS.len() - 1) % LABELS.len(),
+            KeyCode::Down | KeyCode::Tab => self.selected = (self.selected + 1) % LABELS.len(),
+            KeyCode::Char(' ') if self.selected == ALL_DAY || self.selected == COMPLETED => {
+                let value = &mut self.values[self.selected];
+                *value = yes_no(*value != "yes");
+            }
+            KeyCode::Char(c) => self.values[self.selected].push(c),
+            KeyCode::Backspace => {
+                self.values[self.selected].pop();
+            }
+            _ => {}
+        }
+        FormAction::Continue
+    }
+
+    /// Writes the form into `event`, leaving it untouched if any field
+    /// doesn't parse.
+    pub fn apply(&self, event: &mut Event) -> Result<(), String> {
+        let mut updated = event.clone();
+        let all_day = self.values[ALL_DAY] == "yes";
+        let tz = match self.values[TIME_ZONE].trim() {
+            _ if all_day => None,
+            "" => None,
+            name if name.eq_ignore_ascii_case("floating") => None,
+            name => Some(name.parse::<Tz>().map_err(|e| format!("Unknown time zone: {}", e))?),
+        };
+
+        let now = time_zone::now_in(tz);
+        let start = date_parser::parse(&self.values[START], now, tz)?;
+        let start = match (start, all_day) {
+            (TimeInput::Date(date), true) => date.and_hms_opt(0, 0, 0).unwrap(),
+            (TimeInput::At(dt), true) => dt.date().and_hms_opt(0, 0, 0).unwrap(),
+            (TimeInput::At(dt), false) => dt,
+            (TimeInput::Date(_), false) => return Err("Start needs a time, or set All day".to_string()),
+        };
+        let end = match (date_parser::parse_end(&self.values[END], start, now, tz)?, all_day) {
+            (TimeInput::Date(date), true) => date.and_hms_opt(0, 0, 0).unwrap(),
+            (TimeInput::At(dt), true) => dt.date().and_hms_opt(0, 0, 0).unwrap(),
+            (TimeInput::At(dt), false) => dt,
+            (TimeInput::Date(_), false) => return Err("End needs a time, or set All day".to_string()),
+        };
+        if end < start {
+            return Err("The event ends before it starts".to_string());
+        }
+
+        updated.set_name(self.values[0].trim().to_string());
+        updated.set_all_day(false);
+        updated.restore_time_zone(tz);
+        updated.set_start_time(start);
+        updated.set_end_time(end);
+        updated.set_all_day(all_day);
+        updated.set_priority(self.values[5].parse::<Priority>()?);
+        updated.set_reoccurance(self.values[6].parse::<Reoccurance>()?);
+        updated.set_note(self.values[7].clone());
+        updated.set_completed(self.values[COMPLETED] == "yes");
+        *event = updated;
+        Ok(())
+    }
+}
+
+fn zone_value(tz: Option<Tz>) -> String {
+    tz.map(|tz| tz.name().to_string()).unwrap_or_else(|| "floating".to_string())
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
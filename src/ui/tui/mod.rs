@@ -0,0 +1,16 @@
+use std::io;
+
+use crate::backend::classes::planner::Planner;
+
+mod app;
+mod form;
+mod view;
+
+/// Runs the full-screen calendar until the user quits, restoring the
+/// terminal afterwards even if drawing fails.
+pub fn run(planner: &mut Planner) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = app::App::new(planner).run(&mut terminal);
+    ratatui::restore();
+    result
+}
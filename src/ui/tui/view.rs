@@ -0,0 +1,196 @@
+use chrono::{Datelike, Days, Months, NaiveDateTime};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, Paragraph, Wrap};
+
+use crate::backend::classes::occurrence::Occurrence;
+use crate::ui::tui::app::{App, Focus};
+use crate::ui::tui::form::{EventForm, LABELS};
+
+const CALENDAR_WIDTH: u16 = 24;
+const DETAIL_HEIGHT: u16 = 9;
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, detail, footer] = Layout::vertical([
+        Constraint::Min(10),
+        Constraint::Length(DETAIL_HEIGHT),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [calendar, agenda] =
+        Layout::horizontal([Constraint::Length(CALENDAR_WIDTH), Constraint::Min(20)]).areas(main);
+
+    draw_calendar(frame, app, calendar);
+    draw_agenda(frame, app, agenda);
+    draw_detail(frame, app, detail);
+    draw_footer(frame, app, footer);
+    if let Some(form) = &app.form {
+        draw_form(frame, form);
+    }
+}
+
+fn focus_block(title: String, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if focused { block.border_style(Style::new().fg(Color::Yellow)) } else { block }
+}
+
+fn draw_calendar(frame: &mut Frame, app: &App, area: Rect) {
+    let first = app.selected_date.with_day(1).unwrap();
+    let days_in_month = ((first + Months::new(1)) - first).num_days() as u32;
+    let offset = first.weekday().num_days_from_monday() as usize;
+
+    let mut lines = vec![Line::from("Mo Tu We Th Fr Sa Su").dim()];
+    let mut week: Vec<Span> = vec![Span::raw("   "); offset];
+    for day in 1..=days_in_month {
+        let date = first.with_day(day).unwrap();
+        let mut style = Style::new();
+        if app.busy_days.contains(&date) {
+            style = style.fg(Color::Cyan).add_modifier(Modifier::BOLD);
+        }
+        if date == app.today {
+            style = style.add_modifier(Modifier::UNDERLINED).fg(Color::Yellow);
+        }
+        if date == app.selected_date {
+            style = style.add_modifier(Modifier::REVERSED);
+        }
+        week.push(Span::styled(format!("{:>2}", day), style));
+        week.push(Span::raw(" "));
+        if (offset + day as usize).is_multiple_of(7) {
+            lines.push(Line::from(std::mem::take(&mut week)));
+        }
+    }
+    if !week.is_empty() {
+        lines.push(Line::from(week));
+    }
+
+    let title = first.format(" %B %Y ").to_string();
+    let block = focus_block(title, app.focus == Focus::Calendar);
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn occurrence_time(occurrence: &Occurrence, app: &App) -> String {
+    let day_start = app.selected_date.and_hms_opt(0, 0, 0).unwrap();
+    let day_end = day_start + Days::new(1);
+    if occurrence.event.is_all_day() {
+        return "all day".to_string();
+    }
+    // Multi-day occurrences continue from or into neighbouring days
+    let clock = |time: NaiveDateTime, outside: bool| {
+        if outside { "..".to_string() } else { time.format("%H:%M").to_string() }
+    };
+    format!(
+        "{}-{}",
+        clock(occurrence.start, occurrence.start < day_start),
+        clock(occurrence.end, occurrence.end > day_end)
+    )
+}
+
+fn draw_agenda(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .agenda
+        .iter()
+        .map(|occurrence| {
+            let check = if occurrence.completed { "[x]" } else { "[ ]" };
+            let line = Line::from(vec![
+                Span::raw(format!("{:<11} {} ", occurrence_time(occurrence, app), check)),
+                Span::raw(occurrence.name.clone()),
+            ]);
+            if occurrence.completed { ListItem::new(line.dim().crossed_out()) } else { ListItem::new(line) }
+        })
+        .collect();
+
+    let title = app.selected_date.format(" %A %Y-%m-%d ").to_string();
+    let block = focus_block(title, app.focus == Focus::Agenda);
+    if items.is_empty() {
+        frame.render_widget(Paragraph::new("No events. Press n to add one.".dim()).block(block), area);
+        return;
+    }
+    let highlight = match app.focus {
+        Focus::Agenda => Style::new().add_modifier(Modifier::REVERSED),
+        Focus::Calendar => Style::new().add_modifier(Modifier::BOLD),
+    };
+    let list = List::new(items).block(block).highlight_style(highlight);
+    frame.render_stateful_widget(list, area, &mut app.agenda_state);
+}
+
+fn draw_detail(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::bordered().title(" Details ");
+    let Some(occurrence) = app.selected_occurrence() else {
+        frame.render_widget(block, area);
+        return;
+    };
+    let event = &occurrence.event;
+    let time_format = if event.is_all_day() { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M" };
+    let zone = event.get_time_zone().map(|tz| tz.name()).unwrap_or("floating");
+    let field = |label: &str, value: String| Line::from(vec![format!("{:<11}", label).bold(), Span::raw(value)]);
+    let lines = vec![
+        field("Event", format!("{} (id {})", occurrence.name, event.get_id())),
+        field(
+            "When",
+            format!(
+                "{} to {}",
+                occurrence.start.format(time_format),
+                occurrence.end.format(time_format)
+            ),
+        ),
+        field("Time zone", zone.to_string()),
+        field("Priority", format!("{:?}", event.get_priority())),
+        field("Repeats", event.get_reoccurance().to_string()),
+        field("Note", event.get_note().clone()),
+        field("Completed", occurrence.completed.to_string()),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), area);
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let text = if app.confirm_delete {
+        "Delete this event and all its occurrences? (y/n)".to_string()
+    } else if !app.status.is_empty() {
+        app.status.clone()
+    } else if app.form.is_some() {
+        "Tab/arrows: field  Space: toggle yes/no  Enter: save  Esc: cancel".to_string()
+    } else {
+        "arrows/hjkl: move  </>: month  t: today  Tab: switch pane  Space: done  e: edit  n: new  d: delete  q: quit"
+            .to_string()
+    };
+    frame.render_widget(Paragraph::new(text).reversed(), area);
+}
+
+fn draw_form(frame: &mut Frame, form: &EventForm) {
+    let width = frame.area().width.saturating_sub(4).min(70);
+    let height = LABELS.len() as u16 + 4;
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+
+    let title = match form.event_id {
+        Some(id) => format!(" Edit event {} ", id),
+        None => " New event ".to_string(),
+    };
+    let mut lines: Vec<Line> = LABELS
+        .iter()
+        .zip(&form.values)
+        .enumerate()
+        .map(|(index, (label, value))| {
+            let line = Line::from(vec![format!("{:<11}", label).bold(), Span::raw(value.clone())]);
+            if index == form.selected { line.reversed() } else { line }
+        })
+        .collect();
+    lines.push(Line::default());
+    if let Some(error) = &form.error {
+        lines.push(Line::from(error.clone()).fg(Color::Red));
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), area);
+    let value = &form.values[form.selected];
+    frame.set_cursor_position((
+        area.x + 1 + 11 + value.chars().count() as u16,
+        area.y + 1 + form.selected as u16,
+    ));
+}
@@ -1,10 +1,27 @@
 use std::io::{self, Write};
-use chrono::NaiveDateTime;
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
 
 use crate::backend::classes::planner::Planner;
+use crate::backend::enums::agenda_range::AgendaRange;
 use crate::backend::enums::reoccurance::Reoccurance;
 use crate::backend::enums::priority::Priority;
+use crate::backend::enums::exchange_format::ExchangeFormat;
+use crate::backend::enums::output_format::OutputFormat;
+use crate::backend::enums::sort_key::SortKey;
+use crate::backend::enums::report_format::ReportFormat;
+use crate::backend::enums::storage_format::StorageFormat;
 use crate::backend::classes::event::Event;
+use crate::backend::classes::occurrence::{self, OccurrenceOverride};
+use crate::backend::date_parser::{self, TimeInput};
+use crate::backend::enums::csv_field::CsvField;
+use crate::backend::exchange;
+use crate::backend::exchange::csv::{self, Column, ColumnMapping, CsvOptions};
+use crate::backend::filter::Filter;
+use crate::backend::storage::atomic::atomic_write;
+use crate::backend::time_zone::{self, Resolution};
+use crate::ui::calendar::{self, Canvas};
+use crate::ui::{output, report};
 
 pub fn display_menu(current_date_time: NaiveDateTime) {
     println!("\n\n  Rust To-Do Planner! Current date-time: {}", current_date_time);
@@ -12,7 +29,15 @@ pub fn display_menu(current_date_time: NaiveDateTime) {
     println!("      2. Create an event");
     println!("      3. Adjust an event");
     println!("      4. Display ALL events");
-    println!("      5. Exit");
+    println!("      5. Change storage format");
+    println!("      6. Restore a backup");
+    println!("      7. Display agenda");
+    println!("      8. Display calendar");
+    println!("      9. Find events");
+    println!("      10. Search events");
+    println!("      11. Import or export events");
+    println!("      12. Write an agenda report");
+    println!("      13. Exit");
 }
 
 pub fn adjust_menu() {
@@ -20,10 +45,24 @@ pub fn adjust_menu() {
     println!("      1. Change name");
     println!("      2. Change start time");
     println!("      3. Change end time");
-    println!("      4. Change reoccurance");
-    println!("      5. Change note");
-    println!("      6. Delete event");
-    println!("      7. Exit event adjustment");
+    println!("      4. Change time zone");
+    println!("      5. Change reoccurance");
+    println!("      6. Change note");
+    println!("      7. Toggle completed");
+    println!("      8. Toggle all day");
+    println!("      9. Adjust a single occurrence");
+    println!("      10. Delete event");
+    println!("      11. Exit event adjustment");
+}
+
+pub fn occurrence_menu() {
+    println!("\n  What would you like to do with this occurrence?");
+    println!("      1. Toggle completed");
+    println!("      2. Skip this occurrence");
+    println!("      3. Move this occurrence");
+    println!("      4. Rename this occurrence");
+    println!("      5. Restore this occurrence");
+    println!("      6. Back");
 }
 
 pub fn get_choice() -> Option<usize> {
@@ -60,23 +99,136 @@ fn id_input() -> Option<usize> {
     }
 }
 
-pub fn get_unix_timestamp_from_input() -> Option<NaiveDateTime> {
+/// Shows what a typed date or time was understood as.
+fn echo_time_input(time: &TimeInput) {
+    match time {
+        TimeInput::At(dt) => println!("  -> {}", dt.format("%a %Y-%m-%d %H:%M")),
+        TimeInput::Date(date) => println!("  -> {} (all day)", date.format("%a %Y-%m-%d")),
+    }
+}
+
+fn read_time_input(parse: impl FnOnce(&str) -> Result<TimeInput, String>) -> Option<TimeInput> {
     let mut input = String::new();
     if io::stdin().read_line(&mut input).is_err() {
         println!("Error reading input.");
         return None;
     }
-    let input = input.trim();
 
-    match NaiveDateTime::parse_from_str(input, "%m-%d-%Y %H:%M") {
-        Ok(dt) => Some(dt),
+    match parse(&input) {
+        Ok(time) => {
+            echo_time_input(&time);
+            Some(time)
+        }
         Err(e) => {
-            println!("Failed to parse date/time: {}", e);
+            println!("{}", e);
+            None
+        }
+    }
+}
+
+/// Reads a date and/or time in `zone`, such as `03-14-2026 15:00`,
+/// `tomorrow 3pm`, `next friday`, `in 2 hours` or `2026-03-14T15:00`, and
+/// echoes what it resolved to. A bare date is returned as `TimeInput::Date`.
+pub fn get_unix_timestamp_from_input(zone: Option<Tz>) -> Option<TimeInput> {
+    read_time_input(|input| date_parser::parse(input, time_zone::now_in(zone), zone))
+}
+
+/// Like `get_unix_timestamp_from_input` but insists on a time of day.
+fn get_date_time_from_input(zone: Option<Tz>) -> Option<NaiveDateTime> {
+    require_time(get_unix_timestamp_from_input(zone)?)
+}
+
+/// Reads an end time, which may also be a duration after `start` (`+45m`).
+fn get_end_time_from_input(zone: Option<Tz>, start: NaiveDateTime) -> Option<NaiveDateTime> {
+    require_time(read_time_input(|input| {
+        date_parser::parse_end(input, start, time_zone::now_in(zone), zone)
+    })?)
+}
+
+fn require_time(time: TimeInput) -> Option<NaiveDateTime> {
+    match time {
+        TimeInput::At(dt) => Some(dt),
+        TimeInput::Date(_) => {
+            println!("Please include a time (e.g. 3pm or 15:00).");
             None
         }
     }
 }
 
+fn zone_label(tz: Option<Tz>) -> &'static str {
+    tz.map(|tz| tz.name()).unwrap_or("floating time")
+}
+
+/// Reads an IANA zone name. Blank means the system zone and `floating`
+/// means no zone at all; `None` is returned on bad input.
+fn get_time_zone_from_input() -> Option<Option<Tz>> {
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Error reading input.");
+        return None;
+    }
+    match input.trim() {
+        "" => {
+            let local = time_zone::local_zone();
+            if local.is_none() {
+                println!("Couldn't determine the system time zone, using floating time.");
+            }
+            Some(local)
+        }
+        name if name.eq_ignore_ascii_case("floating") => Some(None),
+        name => match name.parse::<Tz>() {
+            Ok(tz) => Some(Some(tz)),
+            Err(e) => {
+                println!("Unknown time zone: {}", e);
+                None
+            }
+        },
+    }
+}
+
+/// Turns a wall-clock time in `tz` into the instant an `Event` stores,
+/// asking the user what they meant when a DST change makes it ambiguous
+/// or skips it entirely.
+fn resolve_wall_time(tz: Option<Tz>, wall: NaiveDateTime) -> Option<NaiveDateTime> {
+    let Some(tz) = tz else {
+        return Some(wall);
+    };
+    match time_zone::resolve(tz, wall) {
+        Resolution::Exact(dt) => Some(dt.naive_utc()),
+        Resolution::Ambiguous(earlier, later) => {
+            println!("{} happens twice in {} because the clocks go back. Which one?", wall, tz.name());
+            println!("      1. {}", earlier);
+            println!("      2. {}", later);
+            match get_choice() {
+                Some(1) => Some(earlier.naive_utc()),
+                Some(2) => Some(later.naive_utc()),
+                _ => {
+                    println!("Invalid choice.");
+                    None
+                }
+            }
+        }
+        Resolution::Gap(shifted) => {
+            print!(
+                "{} doesn't exist in {} because the clocks go forward. Use {} instead? (y/n):\n> ",
+                wall,
+                tz.name(),
+                shifted.naive_local()
+            );
+            io::stdout().flush().expect("Failed to flush stdout");
+            let mut confirmation = String::new();
+            if io::stdin().read_line(&mut confirmation).is_err() {
+                println!("Error reading input.");
+                return None;
+            }
+            match confirmation.trim().to_lowercase().as_str() {
+                "y" | "yes" => Some(shifted.naive_utc()),
+                _ => None,
+            }
+        }
+    }
+}
+
 pub fn event_creater(planner: &mut Planner) {
     // Get event name
     print!("Enter event name: ");
@@ -88,10 +240,20 @@ pub fn event_creater(planner: &mut Planner) {
     }
     let name = name.trim().to_string();
 
-    // Get start time
-    print!("Enter start time (MM-DD-YYYY HH:MM): ");
+    // Get time zone
+    print!("Enter time zone (e.g. Europe/Paris, blank for this system's zone, or 'floating'): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let Some(tz) = get_time_zone_from_input() else {
+        return;
+    };
+
+    // Get start time, a bare date makes an all-day event
+    print!(
+        "Enter start in {} (e.g. tomorrow 3pm, next friday 9:30, MM-DD-YYYY HH:MM; a date alone is all day): ",
+        zone_label(tz)
+    );
     io::stdout().flush().expect("Failed to flush stdout");
-    let start_time = match get_unix_timestamp_from_input() {
+    let start = match get_unix_timestamp_from_input(tz) {
         Some(time) => time,
         None => {
             println!("Invalid start time input.");
@@ -99,14 +261,60 @@ pub fn event_creater(planner: &mut Planner) {
         }
     };
 
-    // Get end time
-    print!("Enter end time (MM-DD-YYYY HH:MM): ");
-    io::stdout().flush().expect("Failed to flush stdout");
-    let end_time = match get_unix_timestamp_from_input() {
-        Some(time) => time,
-        None => {
-            println!("Invalid end time input.");
-            return;
+    let (start_time, end_time, start_instant, end_instant, all_day) = match start {
+        TimeInput::Date(start_date) => {
+            // Get end date
+            print!("Enter end date (e.g. sunday, MM-DD-YYYY; blank for a single day): ");
+            io::stdout().flush().expect("Failed to flush stdout");
+            let mut end_input = String::new();
+            if io::stdin().read_line(&mut end_input).is_err() {
+                println!("Error reading end date.");
+                return;
+            }
+            let end_date = match end_input.trim() {
+                "" => start_date,
+                input => match date_parser::parse(input, time_zone::now_in(None), None) {
+                    Ok(TimeInput::Date(date)) => date,
+                    _ => {
+                        println!("Invalid end date input.");
+                        return;
+                    }
+                },
+            };
+            echo_time_input(&TimeInput::Date(end_date));
+            if end_date < start_date {
+                println!("The end date is before the start date.");
+                return;
+            }
+            let start_time = start_date.and_hms_opt(0, 0, 0).unwrap();
+            let end_time = end_date.and_hms_opt(0, 0, 0).unwrap();
+            (start_time, end_time, start_time, end_time, true)
+        }
+        TimeInput::At(start_time) => {
+            let Some(start_instant) = resolve_wall_time(tz, start_time) else {
+                println!("Invalid start time input.");
+                return;
+            };
+
+            // Get end time
+            print!("Enter end in {} (e.g. 5pm, +45m, +1h30m, MM-DD-YYYY HH:MM): ", zone_label(tz));
+            io::stdout().flush().expect("Failed to flush stdout");
+            let end_time = match get_end_time_from_input(tz, start_time) {
+                Some(time) => time,
+                None => {
+                    println!("Invalid end time input.");
+                    return;
+                }
+            };
+            let Some(end_instant) = resolve_wall_time(tz, end_time) else {
+                println!("Invalid end time input.");
+                return;
+            };
+            if end_instant < start_instant {
+                println!("The event ends before it starts.");
+                return;
+            }
+            (start_time, end_time, start_instant, end_instant, false)
         }
     };
 
@@ -129,22 +337,17 @@ pub fn event_creater(planner: &mut Planner) {
     };
 
     // Get Reoccurance
-    print!("Enter event reoccurance (None, Daily, Weekly, Monthly, Yearly, Fornite): ");
+    print!("Enter event reoccurance (None, Daily, Weekly, Monthly, Yearly, Fornite, or an RRULE like FREQ=MONTHLY;BYDAY=2TU): ");
     io::stdout().flush().expect("Failed to flush stdout");
     let mut rec_input = String::new();
     if io::stdin().read_line(&mut rec_input).is_err() {
         println!("Error reading reoccurance.");
         return;
     }
-    let reoccurance = match rec_input.trim().to_lowercase().as_str() {
-        "none" => Reoccurance::None,
-        "daily" => Reoccurance::Daily,
-        "weekly" => Reoccurance::Weekly,
-        "monthly" => Reoccurance::Monthly,
-        "yearly" => Reoccurance::Yearly,
-        "fornite" => Reoccurance::Fornite,
-        other => {
-            println!("Unrecognized reoccurance '{}', defaulting to None.", other);
+    let reoccurance = match rec_input.parse::<Reoccurance>() {
+        Ok(reoccurance) => reoccurance,
+        Err(e) => {
+            println!("{}, defaulting to None.", e);
             Reoccurance::None
         }
     };
@@ -161,9 +364,393 @@ pub fn event_creater(planner: &mut Planner) {
 
     let completed = false; // New events are not completed by default
 
+    let mut event = Event::new(0, name, start_time, end_time, priority, reoccurance, note, completed);
+    if all_day {
+        event.set_all_day(true);
+    } else {
+        event.restore_time_zone(tz);
+        event.set_start_instant(start_instant);
+        event.set_end_instant(end_instant);
+    }
+
+    // Confirm what the dates were understood as before saving anything
+    event.display();
+    print!("Create this event? (Y/n): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut confirmation = String::new();
+    if io::stdin().read_line(&mut confirmation).is_err() {
+        println!("Error reading input.");
+        return;
+    }
+    if !matches!(confirmation.trim().to_lowercase().as_str(), "" | "y" | "yes") {
+        println!("Event discarded.");
+        return;
+    }
+
     // Let the planner assign the id automatically, using its create_event method
-    planner.create_event(name, start_time, end_time, priority, reoccurance, note, completed);
-    println!("Event created successfully and added to the planner.");
+    match planner.create_event(event) {
+        Ok(_) => println!("Event created successfully and added to the planner."),
+        Err(e) => println!("Failed to save the event: {}", e),
+    }
+}
+
+pub fn change_storage_format(planner: &mut Planner) {
+    print!("Enter new storage format (Json, PrettyJson, Sqlite): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Error reading storage format.");
+        return;
+    }
+    let format = match input.parse::<StorageFormat>() {
+        Ok(StorageFormat::Text) => {
+            println!("Text planners are converted to JSON on load, pick another format.");
+            return;
+        }
+        Ok(format) => format,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    match planner.convert_storage(format) {
+        Ok(()) => println!("Planner now stored as {:?}.", format),
+        Err(e) => println!("Failed to change storage format: {}", e),
+    }
+}
+
+/// Reads which days to show: `week`, a number of days, `month` optionally
+/// followed by one (`month 11-2026`), a range such as `today to friday`, or
+/// a single date.
+fn parse_agenda_range(input: &str) -> Result<AgendaRange, String> {
+    let input = input.trim().to_lowercase();
+    let now = time_zone::now_in(None);
+    let date = |input: &str| match date_parser::parse(input, now, None)? {
+        TimeInput::Date(date) => Ok(date),
+        TimeInput::At(dt) => Ok(dt.date()),
+    };
+    if input.is_empty() || input == "week" || input == "this week" {
+        return Ok(AgendaRange::Week);
+    }
+    if let Ok(days) = input.parse::<u32>() {
+        return Ok(AgendaRange::Days(days));
+    }
+    if let Some(month) = input.strip_prefix("month") {
+        let month = if month.trim().is_empty() { "this month" } else { month };
+        return date_parser::parse_month(month, now, None).map(AgendaRange::Month);
+    }
+    // MM-YYYY or YYYY-MM, as opposed to a full date
+    let is_month = input.matches('-').count() == 1 && input.chars().all(|c| c.is_ascii_digit() || c == '-');
+    if is_month || input.contains("month") {
+        return date_parser::parse_month(&input, now, None).map(AgendaRange::Month);
+    }
+    match input.split_once(" to ") {
+        Some((first, last)) => Ok(AgendaRange::Between(date(first)?, date(last)?)),
+        None => date(&input).map(|day| AgendaRange::Between(day, day)),
+    }
+}
+
+fn agenda_dates(range: AgendaRange, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+    range.dates(today).ok_or_else(|| "That range runs past the last supported date.".to_string())
+}
+
+pub fn show_agenda(planner: &Planner) {
+    print!("Show which days? (week, a number of days, month [MM-YYYY], or a range like 'today to friday'): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Error reading input.");
+        return;
+    }
+    let now = time_zone::now_in(None);
+    match parse_agenda_range(&input).and_then(|range| agenda_dates(range, now.date())) {
+        Ok((first, last)) => planner.agenda(first, last, now).display(),
+        Err(e) => println!("{}", e),
+    }
+}
+
+pub fn show_calendar(planner: &Planner) {
+    print!("Enter 'month' or 'week', optionally followed by a date (e.g. week next monday): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Error reading input.");
+        return;
+    }
+    let input = input.trim().to_lowercase();
+    let (week, rest) = match input.split_once(' ') {
+        Some((view, rest)) => (view == "week", rest),
+        None => (input == "week", ""),
+    };
+    let now = time_zone::now_in(None);
+    let date = match rest.trim() {
+        "" => now.date(),
+        rest => match date_parser::parse(rest, now, None) {
+            Ok(TimeInput::Date(date)) => date,
+            Ok(TimeInput::At(dt)) => dt.date(),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        },
+    };
+    let canvas = Canvas::detect();
+    if week {
+        println!("{}", calendar::render_week(planner, date, now.date(), &canvas));
+    } else {
+        println!("{}", calendar::render_month(planner, date, now.date(), &canvas));
+    }
+}
+
+pub fn write_report(planner: &Planner) {
+    let Some(path) = prompt_line("File name (e.g. standup.md or agenda.html): ") else {
+        return;
+    };
+    let format = match ReportFormat::from_path(&path) {
+        Ok(format) => format,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let prompt = "Which days? (week, a number of days, month [MM-YYYY], or a range like 'today to friday'): ";
+    let Some(input) = prompt_line(prompt) else {
+        return;
+    };
+    let today = time_zone::now_in(None).date();
+    let (first, last) = match parse_agenda_range(&input).and_then(|range| agenda_dates(range, today)) {
+        Ok(dates) => dates,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+    let text = report::render(planner, format, first, last, time_zone::now_in(None));
+    match atomic_write(&path, |out| out.write_all(text.as_bytes())) {
+        Ok(()) => println!("Wrote the agenda for {} to {} to {}.", first, last, path),
+        Err(e) => println!("Failed to write {}: {}", path, e),
+    }
+}
+
+fn prompt_line(prompt: &str) -> Option<String> {
+    print!("{}", prompt);
+    io::stdout().flush().expect("Failed to flush stdout");
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        println!("Error reading input.");
+        return None;
+    }
+    Some(input.trim().to_string())
+}
+
+pub fn find_events(planner: &Planner) {
+    let prompt = "Filter (e.g. priority:high and not done and name~\"review\", blank for all): ";
+    let Some(input) = prompt_line(prompt) else {
+        return;
+    };
+    let filter = if input.is_empty() {
+        None
+    } else {
+        match Filter::parse(&input, time_zone::now_in(None)) {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        }
+    };
+
+    let prompt = "Sort by (start, end, name, priority or id, - to reverse; blank for start): ";
+    let Some(input) = prompt_line(prompt) else {
+        return;
+    };
+    let sort = if input.is_empty() {
+        SortKey::default()
+    } else {
+        match input.parse::<SortKey>() {
+            Ok(sort) => sort,
+            Err(e) => {
+                println!("{}", e);
+                return;
+            }
+        }
+    };
+
+    let Some(input) = prompt_line("Show at most how many? (blank for all): ") else {
+        return;
+    };
+    let limit = if input.is_empty() {
+        None
+    } else {
+        match input.parse::<usize>() {
+            Ok(limit) => Some(limit),
+            Err(_) => {
+                println!("Invalid number.");
+                return;
+            }
+        }
+    };
+
+    let events = planner.query(filter.as_ref(), sort, limit);
+    if events.is_empty() {
+        println!("No matching events.");
+    }
+    for event in &events {
+        event.display();
+    }
+}
+
+pub fn search_events(planner: &Planner) {
+    let Some(query) = prompt_line("Search names and notes for: ") else {
+        return;
+    };
+    if query.is_empty() {
+        println!("Nothing to search for.");
+        return;
+    }
+    let hits = planner.search(&query);
+    if let Err(e) = output::print_search(OutputFormat::Text, &hits, output::color_enabled()) {
+        println!("Failed to print results: {}", e);
+    }
+}
+
+/// Asks which column holds each of the main fields of a CSV file, offering
+/// the columns named after them, and how its dates are written.
+fn csv_options_from_input(text: &str) -> Option<CsvOptions> {
+    let mut options = CsvOptions::default();
+    let headers = match csv::headers(text, &options) {
+        Ok(headers) => headers,
+        Err(e) => {
+            println!("{}", e);
+            return None;
+        }
+    };
+    println!("Columns:");
+    for (index, header) in headers.iter().enumerate() {
+        println!("      {}. {}", index + 1, header);
+    }
+
+    let fields = [
+        CsvField::Name,
+        CsvField::Start,
+        CsvField::End,
+        CsvField::Priority,
+        CsvField::Note,
+        CsvField::Completed,
+    ];
+    for field in fields {
+        let guess = headers.iter().position(|h| field.matches_header(h));
+        let prompt = match guess {
+            Some(index) => format!("Column for {} (blank for {}. {}): ", field, index + 1, headers[index]),
+            None => format!("Column for {} (blank for none): ", field),
+        };
+        let input = prompt_line(&prompt)?;
+        let position = if input.is_empty() {
+            guess.map(|index| index + 1)
+        } else {
+            match input.parse::<usize>() {
+                Ok(position) if (1..=headers.len()).contains(&position) => Some(position),
+                _ => {
+                    println!("Invalid column number.");
+                    return None;
+                }
+            }
+        };
+        if let Some(position) = position {
+            options.mappings.push(ColumnMapping { field, column: Column::Position(position) });
+        }
+    }
+
+    let format = prompt_line("Date format, e.g. %d/%m/%Y %H:%M (blank to recognise them): ")?;
+    if !format.is_empty() {
+        options.date_formats.push(format);
+    }
+    Some(options)
+}
+
+pub fn import_export(planner: &mut Planner) {
+    let Some(choice) = prompt_line("Import from or export to a file? (i/e): ") else {
+        return;
+    };
+    let import = match choice.to_lowercase().as_str() {
+        "i" | "import" => true,
+        "e" | "export" => false,
+        _ => {
+            println!("Invalid choice.");
+            return;
+        }
+    };
+    let Some(path) = prompt_line("File name (e.g. calendar.ics): ") else {
+        return;
+    };
+    let format = match ExchangeFormat::from_path(&path) {
+        Ok(format) => format,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    if import {
+        let text = match exchange::read_text(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                println!("Failed to read {}: {}", path, e);
+                return;
+            }
+        };
+        let options = if format == ExchangeFormat::Csv {
+            match csv_options_from_input(&text) {
+                Some(options) => options,
+                None => return,
+            }
+        } else {
+            CsvOptions::default()
+        };
+        planner.import_events(exchange::parse(&text, format, &options), false).display();
+    } else {
+        let events = planner.query(None, SortKey::default(), None);
+        match exchange::write_file(Some(&path), format, &events) {
+            Ok(()) => println!("Exported {} event(s) to {}.", events.len(), path),
+            Err(e) => println!("Failed to write {}: {}", path, e),
+        }
+    }
+}
+
+pub fn restore_backup(planner: &mut Planner) {
+    let backups = match planner.list_backups() {
+        Ok(backups) => backups,
+        Err(e) => {
+            println!("Failed to list backups: {}", e);
+            return;
+        }
+    };
+    if backups.is_empty() {
+        println!("No backups found.");
+        return;
+    }
+
+    println!("Select a backup to restore (0 to cancel):");
+    for (index, backup) in backups.iter().enumerate() {
+        println!("      {}. {}", index + 1, backup.display());
+    }
+    let backup = match get_choice() {
+        Some(0) | None => {
+            println!("Restore canceled.");
+            return;
+        }
+        Some(choice) => match backups.get(choice - 1) {
+            Some(backup) => backup,
+            None => {
+                println!("Invalid backup number {}.", choice);
+                return;
+            }
+        },
+    };
+    match planner.restore_backup(backup) {
+        Ok(()) => println!("Restored planner from {}.", backup.display()),
+        Err(e) => println!("Failed to restore backup: {}", e),
+    }
 }
 
 pub fn adjust_event(planner: &mut Planner) {
@@ -176,6 +763,7 @@ pub fn adjust_event(planner: &mut Planner) {
     };
 
     if let Some(event) = planner.find_event_mut(event_id) {
+        let mut deleted = false;
         loop {
             event.display();
             adjust_menu();
@@ -183,13 +771,17 @@ pub fn adjust_event(planner: &mut Planner) {
                 Some(1) => change_name(event),
                 Some(2) => change_start_time(event),
                 Some(3) => change_end_time(event),
-                Some(4) => change_reoccurance(event),
-                Some(5) => change_note(event),
-                Some(6) => {
-                    delete_event(planner, event_id);
+                Some(4) => change_time_zone(event),
+                Some(5) => change_reoccurance(event),
+                Some(6) => change_note(event),
+                Some(7) => toggle_completed(event),
+                Some(8) => toggle_all_day(event),
+                Some(9) => adjust_occurrence(event),
+                Some(10) => {
+                    deleted = delete_event(planner, event_id);
                     break;
                 },
-                Some(7) => {
+                Some(11) => {
                     println!("Exiting adjust menu.");
                     break;
                 },
@@ -198,7 +790,7 @@ pub fn adjust_event(planner: &mut Planner) {
         }
 
         // Save changes after mutable borrow ends
-        if let Err(e) = planner.save_events_to_file() {
+        if !deleted && let Err(e) = planner.update_event(event_id) {
             println!("Failed to save changes: {}", e);
         }
     } else {
@@ -219,60 +811,77 @@ fn change_name(event: &mut Event) {
 
 }
 
-fn change_start_time(event: &mut Event) {
-    print!("Enter new start time (MM-DD-YYYY HH:MM): ");
+/// Reads a new start or end for `event`: a bare date for all-day events,
+/// otherwise a date-time in the event's zone. Returns the stored instant.
+fn get_event_time_from_input(event: &Event, which: &str) -> Option<NaiveDateTime> {
+    let tz = event.get_time_zone();
+    if event.is_all_day() {
+        print!("Enter new {} date (e.g. friday, MM-DD-YYYY): ", which);
+    } else {
+        print!("Enter new {} in {} (e.g. tomorrow 3pm, MM-DD-YYYY HH:MM): ", which, zone_label(tz));
+    }
     io::stdout().flush().expect("Failed to flush stdout");
 
-    if let Some(new_time) = get_unix_timestamp_from_input() {
-        event.set_start_time(new_time);
-        println!("Start time updated to: {}", event.get_start_time());
+    match (get_unix_timestamp_from_input(tz)?, event.is_all_day()) {
+        (TimeInput::Date(date), true) => date.and_hms_opt(0, 0, 0),
+        (TimeInput::At(time), false) => resolve_wall_time(tz, time),
+        (TimeInput::At(_), true) => {
+            println!("This is an all-day event, enter just a date.");
+            None
+        }
+        (TimeInput::Date(_), false) => {
+            println!("Please include a time (HH:MM).");
+            None
+        }
+    }
+}
+
+fn change_start_time(event: &mut Event) {
+    if let Some(instant) = get_event_time_from_input(event, "start") {
+        if *event.get_end_instant() < instant {
+            println!("The event would end before it starts.");
+            return;
+        }
+        event.set_start_instant(instant);
+        println!("Start time updated to: {} ({})", event.get_start_time(), zone_label(event.get_time_zone()));
     }
 }
 
 fn change_end_time(event: &mut Event) {
-    print!("Enter new end time (MM-DD-YYYY HH:MM): ");
+    if let Some(instant) = get_event_time_from_input(event, "end") {
+        if instant < *event.get_start_instant() {
+            println!("The event would end before it starts.");
+            return;
+        }
+        event.set_end_instant(instant);
+        println!("End time updated to: {} ({})", event.get_end_time(), zone_label(event.get_time_zone()));
+    }
+}
+
+fn change_time_zone(event: &mut Event) {
+    print!("Enter new time zone (e.g. Europe/Paris, blank for this system's zone, or 'floating'): ");
     io::stdout().flush().expect("Failed to flush stdout");
 
-    if let Some(new_time) = get_unix_timestamp_from_input() {
-        event.set_end_time(new_time);
-        println!("End time updated to: {}", event.get_end_time());
+    if let Some(tz) = get_time_zone_from_input() {
+        event.set_time_zone(tz);
+        println!("Time zone updated to: {}", zone_label(tz));
     }
 }
 
 fn change_reoccurance(event: &mut Event) {
-    println!("Enter new reoccurance option (None, Daily, Weekly, Monthly, Yearly, Fornite): ");
+    println!("Enter new reoccurance option (None, Daily, Weekly, Monthly, Yearly, Fornite, or an RRULE like FREQ=DAILY;INTERVAL=3;COUNT=10): ");
     io::stdout().flush().expect("Failed to flush stdout");
     let mut input = String::new();
     if let Err(e) = io::stdin().read_line(&mut input) {
           println!("Error reading input: {}", e);
           return;
     }
-    match input.trim().to_lowercase().as_str() {
-          "none" => {
-               event.set_reoccurance(Reoccurance::None);
-               println!("Reoccurance updated.");
-          },
-          "daily" => {
-               event.set_reoccurance(Reoccurance::Daily);
-               println!("Reoccurance updated.");
-          },
-          "weekly" => {
-               event.set_reoccurance(Reoccurance::Weekly);
-               println!("Reoccurance updated.");
-          },
-          "monthly" => {
-               event.set_reoccurance(Reoccurance::Monthly);
+    match input.parse::<Reoccurance>() {
+          Ok(reoccurance) => {
+               event.set_reoccurance(reoccurance);
                println!("Reoccurance updated.");
           },
-          "yearly" => {
-               event.set_reoccurance(Reoccurance::Yearly);
-               println!("Reoccurance updated.");
-          },
-          "fornite" => {
-               event.set_reoccurance(Reoccurance::Fornite);
-               println!("Reoccurance updated.");
-          },
-          _ => println!("Unknown reoccurance option."),
+          Err(e) => println!("Unknown reoccurance option: {}", e),
     }
 }
 
@@ -288,22 +897,139 @@ fn change_note(event: &mut Event) {
     println!("Note updated.");
 }
 
-fn delete_event(planner: &mut Planner, event_id: usize) {
+fn toggle_completed(event: &mut Event) {
+    event.set_completed(!event.get_completed());
+    println!("Completed set to: {}", event.get_completed());
+}
+
+/// All-day events keep their dates at midnight in floating time; timed
+/// ones made from them start and end at midnight until the times are changed.
+fn toggle_all_day(event: &mut Event) {
+    event.set_all_day(!event.is_all_day());
+    if event.is_all_day() {
+        println!("All day set to: true");
+    } else {
+        println!("All day set to: false. The event runs from midnight until its start and end are changed.");
+    }
+}
+
+fn get_date_from_input(zone: Option<Tz>) -> Option<NaiveDate> {
+    match get_unix_timestamp_from_input(zone)? {
+        TimeInput::Date(date) => Some(date),
+        TimeInput::At(dt) => Some(dt.date()),
+    }
+}
+
+fn adjust_occurrence(event: &mut Event) {
+    if !event.is_recurring() {
+        println!("This event doesn't repeat, toggle its completion instead.");
+        return;
+    }
+
+    print!("Enter occurrence date (e.g. today, next monday, MM-DD-YYYY): ");
+    io::stdout().flush().expect("Failed to flush stdout");
+    let Some(date) = get_date_from_input(event.get_time_zone()) else {
+        return;
+    };
+    let day_start = date.and_hms_opt(0, 0, 0).unwrap();
+    let Some(original_start) = occurrence::original_starts(event, day_start, day_start + Days::new(1))
+        .into_iter()
+        .next()
+    else {
+        println!("Event doesn't occur on {}.", date);
+        return;
+    };
+    let duration = event.get_end_time() - event.get_start_time();
+
+    loop {
+        let current = event.get_occurrences().overrides.get(&original_start).cloned();
+        println!("\n  Occurrence originally at {}", original_start);
+        if event.get_occurrences().exceptions.contains(&original_start) {
+            println!("  (skipped)");
+        } else if let Some(moved) = &current {
+            println!("  (moved to {} - {})", moved.start, moved.end);
+        }
+        occurrence_menu();
+        match get_choice() {
+            Some(1) => {
+                let completed = event.get_occurrences().completed.contains(&original_start);
+                event.set_occurrence_completed(original_start, !completed);
+                println!("Occurrence completed set to: {}", !completed);
+            }
+            Some(2) => {
+                event.skip_occurrence(original_start);
+                println!("Occurrence skipped.");
+            }
+            Some(3) => {
+                let tz = event.get_time_zone();
+                print!("Enter new start in {} (e.g. tomorrow 3pm, MM-DD-YYYY HH:MM): ", zone_label(tz));
+                io::stdout().flush().expect("Failed to flush stdout");
+                let Some(start) = get_date_time_from_input(tz) else { continue };
+                print!("Enter new end in {} (e.g. 5pm, +45m, MM-DD-YYYY HH:MM): ", zone_label(tz));
+                io::stdout().flush().expect("Failed to flush stdout");
+                let Some(end) = get_end_time_from_input(tz, start) else { continue };
+                if end < start {
+                    println!("The occurrence would end before it starts.");
+                    continue;
+                }
+                let name = current.and_then(|c| c.name);
+                event.override_occurrence(original_start, OccurrenceOverride { start, end, name });
+                println!("Occurrence moved.");
+            }
+            Some(4) => {
+                print!("Enter new name for this occurrence: ");
+                io::stdout().flush().expect("Failed to flush stdout");
+                let mut name = String::new();
+                if io::stdin().read_line(&mut name).is_err() {
+                    println!("Error reading input for name.");
+                    continue;
+                }
+                let (start, end) = match current {
+                    Some(c) => (c.start, c.end),
+                    None => (original_start, original_start + duration),
+                };
+                let name = Some(name.trim().to_string());
+                event.override_occurrence(original_start, OccurrenceOverride { start, end, name });
+                println!("Occurrence renamed.");
+            }
+            Some(5) => {
+                event.reset_occurrence(original_start);
+                println!("Occurrence restored.");
+            }
+            Some(6) => break,
+            _ => println!("Invalid option, please try again."),
+        }
+    }
+}
+
+fn delete_event(planner: &mut Planner, event_id: usize) -> bool {
     print!("Are you sure you want to delete event ID {}? (y/n):\n> ", event_id);
     io::stdout().flush().expect("Failed to flush stdout");
     let mut confirmation = String::new();
     if io::stdin().read_line(&mut confirmation).is_err() {
         println!("Error reading input.");
-        return;
+        return false;
     }
     match confirmation.trim().to_lowercase().as_str() {
         "y" | "yes" => {
-            if let Some(_deleted_event) = planner.delete_event(event_id) {
-                println!("Event deleted successfully.");
-            } else {
-                println!("Failed to delete event. Make sure the event ID is valid.");
+            match planner.delete_event(event_id) {
+                Ok(Some(_deleted_event)) => {
+                    println!("Event deleted successfully.");
+                    true
+                }
+                Ok(None) => {
+                    println!("Failed to delete event. Make sure the event ID is valid.");
+                    false
+                }
+                Err(e) => {
+                    println!("Failed to delete event: {}", e);
+                    false
+                }
             }
         },
-        _ => println!("Deletion canceled."),
+        _ => {
+            println!("Deletion canceled.");
+            false
+        },
     }
 }
\ No newline at end of file